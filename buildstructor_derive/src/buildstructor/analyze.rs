@@ -0,0 +1,656 @@
+use crate::buildstructor::utils::TypeExt;
+use proc_macro2::Span;
+use quote::{format_ident, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Attribute, Expr, ExprLit, ExprPath, FnArg, Generics, Ident, ImplItem, ImplItemFn,
+    ItemImpl, Lit, LitBool, LitStr, Meta, MetaNameValue, Path, Result, ReturnType, Token, Type,
+    Visibility,
+};
+
+use crate::parse::Ast;
+pub struct BuilderModel {
+    pub impl_name: Ident,
+    pub impl_generics: Generics,
+    pub delegate_name: Ident,
+    pub delegate_generics: Generics,
+    pub delegate_args: Vec<FnArg>,
+    pub field_configs: Vec<FieldConfig>,
+    pub delegate_return_type: ReturnType,
+    pub is_async: bool,
+    pub vis: Visibility,
+    pub config: BuilderConfig,
+    pub attributes: Vec<Attribute>,
+    pub self_ty: Box<Type>,
+    pub overridable: bool,
+    pub krate: Path,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BuildstructorConfig {
+    pub overridable: bool,
+    // The path to the runtime crate, for when it has been renamed or re-exported.
+    pub krate: Option<Path>,
+}
+
+impl Parse for BuildstructorConfig {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut config = BuildstructorConfig::default();
+        for meta in input.parse_terminated(Meta::parse, Token![,])? {
+            match (meta.path().to_token_stream().to_string().as_str(), &meta) {
+                ("overridable", Meta::Path(_)) => config.overridable = true,
+                (
+                    "crate",
+                    Meta::NameValue(MetaNameValue {
+                        value: Expr::Path(ExprPath { path, .. }),
+                        ..
+                    }),
+                ) => config.krate = Some(path.clone()),
+                (name, _) => {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        format!(
+                            "invalid buildstructor attribute '{}', only 'overridable' and 'crate' are allowed, 'crate' must be a path",
+                            name
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Default)]
+pub struct BuilderConfig {
+    pub entry: Option<String>,
+    pub exit: Option<String>,
+    pub span: Option<Span>,
+    pub visibility: Option<String>,
+    pub to_builder: Option<Ident>,
+    pub dynamic: bool,
+    pub into_future: Option<LitBool>,
+    pub blocking_exit: Option<Ident>,
+    pub exit_arc: Option<Ident>,
+    pub exit_boxed: Option<Ident>,
+    pub exits: Vec<ExitConfig>,
+}
+impl Parse for BuilderConfig {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut config = BuilderConfig {
+            span: Some(input.span()),
+            ..Default::default()
+        };
+        for meta in input.parse_terminated(Meta::parse, Token![,])? {
+            let name = meta.path().to_token_stream().to_string();
+            let name_value = match meta {
+                Meta::Path(_) if name == "dynamic" => {
+                    config.dynamic = true;
+                    continue;
+                }
+                Meta::List(list) if name == "exit" => {
+                    config.exits.push(list.parse_args()?);
+                    continue;
+                }
+                Meta::NameValue(name_value) => name_value,
+                _ => return Err(syn::Error::new(
+                    meta.span(),
+                    format!("invalid builder attribute '{}', only 'entry', 'exit', 'exit_arc', 'exit_boxed', 'blocking_exit', 'visibility', 'to_builder', 'into_future' and 'dynamic' are allowed", name),
+                )),
+            };
+            let value = &name_value.value;
+            match (name.as_str(), value) {
+                ("entry", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.entry = Some(value.value());
+                }
+                ("exit", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.exit = Some(value.value());
+                }
+                ("visibility", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    let value = value.value();
+                    config.visibility = Some(value);
+                }
+                ("to_builder", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.to_builder = Some(value.parse()?);
+                }
+                ("exit_arc", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.exit_arc = Some(value.parse()?);
+                }
+                ("exit_boxed", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.exit_boxed = Some(value.parse()?);
+                }
+                ("blocking_exit", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    config.blocking_exit = Some(value.parse()?);
+                }
+                ("into_future", Expr::Lit(ExprLit{lit:Lit::Bool(value), ..})) => {
+                    config.into_future = Some(value.clone());
+                }
+                _ => return Err(syn::Error::new(
+                    value.span(),
+                    format!("invalid builder attribute '{}', only 'entry', 'exit', 'exit_arc', 'exit_boxed', 'blocking_exit', 'visibility', 'to_builder', 'into_future' and 'dynamic' are allowed, 'into_future' must be a bool and the others must be strings", name),
+                )),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// An additional exit, `exit(name = "<name>", map = <expr>, output = "<type>")`, that maps the value returned by the delegate.
+/// `output` is required as the macro only sees the `map` expression and cannot infer what it returns.
+pub struct ExitConfig {
+    pub name: Ident,
+    pub map: Expr,
+    pub output: Type,
+}
+impl Parse for ExitConfig {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let span = input.span();
+        let mut name = None;
+        let mut map = None;
+        let mut output = None;
+        for name_value in input.parse_terminated(MetaNameValue::parse, Token![,])? {
+            let key = name_value.path.to_token_stream().to_string();
+            match (key.as_str(), &name_value.value) {
+                ("name", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    name = Some(value.parse()?);
+                }
+                ("map", value) => {
+                    map = Some(value.clone());
+                }
+                ("output", Expr::Lit(ExprLit{lit:Lit::Str(value), ..})) => {
+                    output = Some(value.parse()?);
+                }
+                _ => return Err(syn::Error::new(
+                    name_value.span(),
+                    format!("invalid exit attribute '{}', only 'name', 'map' and 'output' are allowed, 'name' and 'output' must be strings", key),
+                )),
+            }
+        }
+        match (name, map, output) {
+            (Some(name), Some(map), Some(output)) => Ok(ExitConfig { name, map, output }),
+            (Some(_), Some(_), None) => Err(syn::Error::new(
+                span,
+                "exit requires 'output' as the type returned by 'map' cannot be inferred, e.g. exit(name = \"build_shared\", map = Arc::new, output = \"Arc<Self>\")",
+            )),
+            _ => Err(syn::Error::new(
+                span,
+                "exit requires 'name', 'map' and 'output', e.g. exit(name = \"build_shared\", map = Arc::new, output = \"Arc<Self>\")",
+            )),
+        }
+    }
+}
+
+/// Struct level `#[builder(...)]` configuration for `#[derive(Builder)]`.
+/// Everything apart from `constructor` and `crate` is forwarded to the generated constructor's `#[builder]` attribute.
+#[derive(Clone, Default)]
+pub struct DeriveConfig {
+    pub constructor: Option<Ident>,
+    pub krate: Option<Path>,
+    pub builder: Vec<Meta>,
+}
+impl Parse for DeriveConfig {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut config = DeriveConfig::default();
+        for meta in input.parse_terminated(Meta::parse, Token![,])? {
+            match (meta.path().to_token_stream().to_string().as_str(), &meta) {
+                (
+                    "constructor",
+                    Meta::NameValue(MetaNameValue {
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(value),
+                                ..
+                            }),
+                        ..
+                    }),
+                ) => {
+                    config.constructor = Some(value.parse()?);
+                }
+                (
+                    "crate",
+                    Meta::NameValue(MetaNameValue {
+                        value: Expr::Path(ExprPath { path, .. }),
+                        ..
+                    }),
+                ) => {
+                    config.krate = Some(path.clone());
+                }
+                (
+                    "entry" | "exit" | "exit_arc" | "exit_boxed" | "visibility" | "to_builder"
+                    | "into_future",
+                    Meta::NameValue(_),
+                )
+                | ("exit", Meta::List(_))
+                | ("dynamic", Meta::Path(_)) => config.builder.push(meta),
+                (name, meta) => {
+                    let span = match meta {
+                        Meta::NameValue(name_value) => name_value.value.span(),
+                        _ => meta.span(),
+                    };
+                    return Err(syn::Error::new(
+                        span,
+                        format!("invalid builder attribute '{}', only 'constructor', 'crate', 'entry', 'exit', 'exit_arc', 'exit_boxed', 'visibility', 'to_builder', 'into_future' and 'dynamic' are allowed", name),
+                    ));
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl DeriveConfig {
+    pub fn merge(&mut self, other: DeriveConfig) {
+        if other.constructor.is_some() {
+            self.constructor = other.constructor;
+        }
+        if other.krate.is_some() {
+            self.krate = other.krate;
+        }
+        self.builder.extend(other.builder);
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.builder.iter().any(|m| m.path().is_ident(name))
+    }
+}
+
+#[derive(Default)]
+pub struct FieldConfig {
+    pub default: Option<Expr>,
+    pub skip: Option<Expr>,
+    pub setter: Option<Ident>,
+    pub singular: Option<Ident>,
+    pub plural: Option<Ident>,
+    pub into: Option<LitBool>,
+    pub element_into: Option<LitBool>,
+    pub key_into: Option<LitBool>,
+    pub value_into: Option<LitBool>,
+    pub overridable: Option<LitBool>,
+    pub group: Option<LitStr>,
+    pub exclusive: Option<LitBool>,
+    pub required: Option<LitBool>,
+    pub at_least_one: Option<LitBool>,
+    pub exit_arg: Option<LitBool>,
+    pub collection: Option<LitStr>,
+    pub insert: Option<Ident>,
+    pub trait_collection: Option<LitBool>,
+    pub trait_map_collection: Option<LitBool>,
+}
+impl Parse for FieldConfig {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let span = input.span();
+        let mut config = FieldConfig::default();
+        for meta in input.parse_terminated(Meta::parse, Token![,])? {
+            let name = meta.path().to_token_stream().to_string();
+            match (name.as_str(), &meta) {
+                ("default", Meta::Path(_)) => {
+                    config.default = Some(parse_quote!(core::default::Default::default()));
+                }
+                ("default", Meta::NameValue(name_value)) => {
+                    config.default = Some(name_value.value.clone());
+                }
+                ("skip", Meta::Path(_)) => {
+                    config.skip = Some(parse_quote!(core::default::Default::default()));
+                }
+                ("skip", Meta::NameValue(name_value)) => {
+                    config.skip = Some(name_value.value.clone());
+                }
+                ("setter", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.setter = Some(value.parse()?);
+                }
+                ("singular", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.singular = Some(value.parse()?);
+                }
+                ("plural", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.plural = Some(value.parse()?);
+                }
+                ("collection", Meta::Path(path)) => {
+                    config.trait_collection = Some(LitBool::new(true, path.span()));
+                }
+                ("map_collection", Meta::Path(path)) => {
+                    config.trait_map_collection = Some(LitBool::new(true, path.span()));
+                }
+                ("collection", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.collection = Some(value.clone());
+                }
+                ("insert", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.insert = Some(value.parse()?);
+                }
+                ("group", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Str(value), ..}), ..})) => {
+                    config.group = Some(value.clone());
+                }
+                ("exclusive" | "required" | "at_least_one", Meta::Path(path)) => {
+                    *config.group_flag_mut(name.as_str()) = Some(LitBool::new(true, path.span()));
+                }
+                ("exclusive" | "required" | "at_least_one", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Bool(value), ..}), ..})) => {
+                    *config.group_flag_mut(name.as_str()) = Some(value.clone());
+                }
+                ("overridable", Meta::Path(path)) => {
+                    config.overridable = Some(LitBool::new(true, path.span()));
+                }
+                ("overridable", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Bool(value), ..}), ..})) => {
+                    config.overridable = Some(value.clone());
+                }
+                ("exit_arg", Meta::Path(path)) => {
+                    config.exit_arg = Some(LitBool::new(true, path.span()));
+                }
+                ("exit_arg", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Bool(value), ..}), ..})) => {
+                    config.exit_arg = Some(value.clone());
+                }
+                ("into" | "element_into" | "key_into" | "value_into", Meta::Path(path)) => {
+                    *config.conversion_mut(name.as_str()) = Some(LitBool::new(true, path.span()));
+                }
+                ("into" | "element_into" | "key_into" | "value_into", Meta::NameValue(MetaNameValue{value: Expr::Lit(ExprLit{lit:Lit::Bool(value), ..}), ..})) => {
+                    *config.conversion_mut(name.as_str()) = Some(value.clone());
+                }
+                _ => return Err(syn::Error::new(
+                    meta.span(),
+                    format!(
+                        "invalid builder attribute '{}', only 'default', 'skip', 'setter', 'singular', 'plural', 'into', 'element_into', 'key_into', 'value_into', 'overridable', 'group', 'exclusive', 'required', 'at_least_one', 'exit_arg', 'collection', 'map_collection' and 'insert' are allowed on parameters, setter, group, collection and insert names must be strings and flags must be a bool",
+                        name
+                    ),
+                )),
+            }
+        }
+
+        if config.default.is_some() && config.skip.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "builder attributes 'default' and 'skip' cannot be used together",
+            ));
+        }
+
+        if let Some(flag) = config
+            .trait_collection
+            .as_ref()
+            .or(config.trait_map_collection.as_ref())
+        {
+            if config.trait_collection.is_some() && config.trait_map_collection.is_some()
+                || config.collection.is_some()
+                || config.insert.is_some()
+            {
+                return Err(syn::Error::new(
+                    flag.span(),
+                    "builder attributes 'collection' and 'map_collection' add items through a trait and cannot be used together with each other, 'collection = \"...\"' or 'insert'",
+                ));
+            }
+        }
+
+        if config.is_exit_arg() && (config.default.is_some() || config.skip.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "builder attribute 'exit_arg' cannot be used together with 'default' or 'skip'",
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+pub fn analyze(legacy_default_builders: bool, ast: &Ast) -> Result<Vec<Result<BuilderModel>>> {
+    let methods = get_eligible_methods(&ast.item, legacy_default_builders);
+    let ident = ast
+        .item
+        .self_ty
+        .raw_ident()
+        .ok_or_else(|| syn::Error::new(ast.item.span(), "cannot find name of struct"))?;
+    let models = methods
+        .into_iter()
+        .map(|(builder, config)| {
+            Ok(BuilderModel {
+                impl_name: ident.clone(),
+                impl_generics: ast.item.generics.clone(),
+                self_ty: ast.item.self_ty.clone(),
+                delegate_name: builder.sig.ident.clone(),
+                delegate_generics: builder.sig.generics.clone(),
+                delegate_args: builder.sig.inputs.clone().into_iter().collect(),
+                field_configs: builder
+                    .sig
+                    .inputs
+                    .iter()
+                    .map(field_config)
+                    .collect::<Result<_>>()?,
+                delegate_return_type: builder.sig.output.clone(),
+                is_async: builder.sig.asyncness.is_some(),
+                vis: builder.vis.clone(),
+                config: config?,
+                attributes: builder.attrs.clone(),
+                // Set from the `#[buildstructor]` attribute once analysis is complete.
+                overridable: false,
+                krate: parse_quote!(buildstructor),
+            })
+        })
+        .collect();
+
+    Ok(models)
+}
+
+fn get_eligible_methods(
+    item: &ItemImpl,
+    default_builders: bool,
+) -> Vec<(&ImplItemFn, Result<BuilderConfig>)> {
+    item.items
+        .iter()
+        .filter_map(|item| {
+            let builder_attr = Some(format_ident!("builder"));
+            if let ImplItem::Fn(method) = item {
+                if let Some(attr) = method
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.path().get_ident() == builder_attr.as_ref())
+                {
+                    return Some((method, {
+                        match attr.meta {
+                            Meta::List(_) => attr.parse_args(),
+                            _ => Ok(BuilderConfig::default()),
+                        }
+                    }));
+                } else if default_builders {
+                    // If the method doesn't have a receiver and it matches the new pattern.
+                    let method_name = method.sig.ident.to_string();
+                    if !matches!(method.sig.inputs.iter().next(), Some(FnArg::Receiver(_)))
+                        && method_name.ends_with("_new")
+                        || method_name.eq("new")
+                    {
+                        return Some((method, Ok(BuilderConfig::default())));
+                    }
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+impl FieldConfig {
+    // Exit args are passed to the exit rather than set on the builder.
+    pub fn is_exit_arg(&self) -> bool {
+        self.exit_arg.as_ref().map(|e| e.value).unwrap_or_default()
+    }
+
+    // Whether the parameter gets a field in the builder state.
+    pub fn is_builder_field(&self) -> bool {
+        self.skip.is_none() && !self.is_exit_arg()
+    }
+
+    fn conversion_mut(&mut self, name: &str) -> &mut Option<LitBool> {
+        match name {
+            "element_into" => &mut self.element_into,
+            "key_into" => &mut self.key_into,
+            "value_into" => &mut self.value_into,
+            _ => &mut self.into,
+        }
+    }
+
+    fn group_flag_mut(&mut self, name: &str) -> &mut Option<LitBool> {
+        match name {
+            "exclusive" => &mut self.exclusive,
+            "at_least_one" => &mut self.at_least_one,
+            _ => &mut self.required,
+        }
+    }
+}
+
+fn field_config(arg: &FnArg) -> Result<FieldConfig> {
+    let builder_attr = Some(format_ident!("builder"));
+    if let FnArg::Typed(arg) = arg {
+        if let Some(attr) = arg
+            .attrs
+            .iter()
+            .find(|attr| attr.path().get_ident() == builder_attr.as_ref())
+        {
+            return match attr.meta {
+                Meta::List(_) => attr.parse_args(),
+                _ => Ok(FieldConfig::default()),
+            };
+        }
+    }
+    Ok(FieldConfig::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildstructor::tests::*;
+
+    #[test]
+    fn single_field_test() {
+        analyze(false, &single_field_test_case()).unwrap();
+    }
+
+    #[test]
+    fn pub_test() {
+        analyze(false, &pub_test_case()).unwrap();
+    }
+
+    #[test]
+    fn multi_field_test() {
+        analyze(false, &multi_field_test_case()).unwrap();
+    }
+
+    #[test]
+    fn generic_test() {
+        analyze(false, &generic_test_case()).unwrap();
+    }
+
+    #[test]
+    fn async_test() {
+        analyze(false, &async_test_case()).unwrap();
+    }
+
+    #[test]
+    fn async_receiver_test() {
+        analyze(false, &async_receiver_test_case()).unwrap();
+    }
+
+    #[test]
+    fn blocking_exit_test() {
+        analyze(false, &blocking_exit_test_case()).unwrap();
+    }
+
+    #[test]
+    fn fallible_test() {
+        analyze(false, &fallible_test_case()).unwrap();
+    }
+
+    #[test]
+    fn exits_test() {
+        analyze(false, &exits_test_case()).unwrap();
+    }
+
+    #[test]
+    fn into_test() {
+        analyze(false, &into_test_case()).unwrap();
+    }
+
+    #[test]
+    fn into_where_test() {
+        analyze(false, &into_where_test_case()).unwrap();
+    }
+
+    #[test]
+    fn option_test() {
+        analyze(false, &option_test_case()).unwrap();
+    }
+
+    #[test]
+    fn collection_test() {
+        analyze(false, &collections_test_case()).unwrap();
+    }
+
+    #[test]
+    fn collection_generics_test() {
+        analyze(false, &collections_generics_test_case()).unwrap();
+    }
+
+    #[test]
+    fn default_test() {
+        analyze(false, &default_test_case()).unwrap();
+    }
+
+    #[test]
+    fn exit_arg_test() {
+        analyze(false, &exit_arg_test_case()).unwrap();
+    }
+
+    #[test]
+    fn qualified_collections_test() {
+        analyze(false, &qualified_collections_test_case()).unwrap();
+    }
+
+    #[test]
+    fn collection_override_test() {
+        analyze(false, &collection_override_test_case()).unwrap();
+    }
+
+    #[test]
+    fn trait_collection_test() {
+        analyze(false, &trait_collection_test_case()).unwrap();
+    }
+
+    #[test]
+    fn optional_collection_test() {
+        analyze(false, &optional_collection_test_case()).unwrap();
+    }
+
+    #[test]
+    fn skip_test() {
+        analyze(false, &skip_test_case()).unwrap();
+    }
+
+    #[test]
+    fn setter_test() {
+        analyze(false, &setter_test_case()).unwrap();
+    }
+
+    #[test]
+    fn into_override_test() {
+        analyze(false, &into_override_test_case()).unwrap();
+    }
+
+    #[test]
+    fn to_builder_test() {
+        analyze(false, &to_builder_test_case()).unwrap();
+    }
+
+    #[test]
+    fn overridable_test() {
+        analyze(false, &overridable_test_case()).unwrap();
+    }
+
+    #[test]
+    fn group_test() {
+        analyze(false, &group_test_case()).unwrap();
+    }
+
+    #[test]
+    fn at_least_one_test() {
+        analyze(false, &at_least_one_test_case()).unwrap();
+    }
+
+    #[test]
+    fn dynamic_test() {
+        analyze(false, &dynamic_test_case()).unwrap();
+    }
+}
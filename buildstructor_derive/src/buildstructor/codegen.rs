@@ -0,0 +1,1771 @@
+use crate::buildstructor::utils::{
+    AngleBracketedGenericArgumentsExt, ExprTupleExt, GenericsExt, IdentExt, TypeExt, TypeTupleExt,
+};
+use crate::lower::{result_types, BuilderField, DelegateParam, Exit, FieldType, Ir};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt as _;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_quote, Expr, GenericArgument, GenericParam, Generics, Index, Lifetime, LifetimeParam,
+    Path, Receiver, Result, ReturnType, Token, Type, TypeReference, TypeTuple, Visibility,
+    WhereClause, WherePredicate,
+};
+extern crate inflector;
+use inflector::Inflector;
+
+pub fn codegen(ir: Ir) -> Result<TokenStream> {
+    let module_name = &ir.module_name;
+    let krate = &ir.krate;
+    let target_name = &ir.impl_name;
+    let builder_alias_name = format_ident!(
+        "{}{}Builder",
+        ir.delegate_name.unraw().to_string().to_pascal_case(),
+        ir.impl_name,
+    );
+
+    let self_ty = &ir.self_ty;
+
+    let (impl_generics, ty_generics, where_clause) = &ir.impl_generics.split_for_impl();
+
+    let param_generics = ir.param_generics();
+
+    let builder_lifetime = ir
+        .receiver
+        .as_ref()
+        .and_then(|r| r.reference.as_ref())
+        .map(|_| Lifetime::new("'__builder", Span::call_site()));
+
+    let builder_lifetime_generics = Generics {
+        params: builder_lifetime
+            .as_ref()
+            .map(|l| {
+                Punctuated::from_iter(vec![GenericParam::Lifetime(LifetimeParam::new(l.clone()))])
+            })
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let builder_generics = Generics::combine(vec![
+        &builder_lifetime_generics,
+        &ir.impl_generics,
+        &ir.delegate_generics,
+        &param_generics,
+    ]);
+    let (builder_impl_generics, _, builder_where_clause) = builder_generics.split_for_impl();
+
+    let builder_generics_tuple = Generics::combine(vec![
+        &builder_lifetime_generics,
+        &ir.impl_generics,
+        &ir.delegate_generics,
+    ]);
+    let builder_tuple_ty_generics = builder_generics_tuple
+        .to_generic_args()
+        .insert(0, Type::Tuple(param_generics.to_tuple_type()));
+
+    let all_generics = Generics::combine(vec![
+        &builder_lifetime_generics,
+        &ir.builder_generics,
+        &ir.impl_generics,
+        &ir.delegate_generics,
+    ]);
+    let (_, all_ty_generics, _) = all_generics.split_for_impl();
+
+    let method_generics = &ir.delegate_generics;
+    let builder_init_generics = Generics::combine(vec![&ir.impl_generics, &ir.delegate_generics]);
+    let builder_init_generic_args = builder_init_generics.to_generic_args().maybe();
+    // A `&self` entry must not hide the lifetime that the builder borrows.
+    let builder_entry_generic_args = builder_init_generics
+        .to_generic_args()
+        .with_elided_lifetime(matches!(
+            &ir.receiver,
+            Some(Receiver {
+                reference: Some((_, None)),
+                ..
+            })
+        ))
+        .maybe();
+    let builder_init_generic_args_with_lifetime = builder_init_generics
+        .to_generic_bounds()
+        .with_implicit_lifetime(ir.implicit_lifetime);
+    let builder_init_generic_args_phantom = builder_init_generic_args.clone().map(|a| {
+        let args: Punctuated<GenericArgument, Token![,]> =
+            Punctuated::from_iter(a.args.into_iter().map(|a| match a {
+                GenericArgument::Lifetime(l) => {
+                    GenericArgument::Type(Type::Reference(TypeReference {
+                        and_token: Default::default(),
+                        lifetime: Some(l),
+                        mutability: None,
+                        elem: Box::new(Type::Tuple(TypeTuple {
+                            paren_token: Default::default(),
+                            elems: Default::default(),
+                        })),
+                    }))
+                }
+                _ => a,
+            }));
+        args
+    });
+    let builder_init_generic_args_with_state = builder_init_generics
+        .to_generic_args()
+        .insert(0, ir.builder_state_type_initial());
+    // A second alias takes the builder state as its last parameter so that partially configured builders can be named.
+    // It can't be a default on the main alias as shorthand projections such as `T::Bar` don't resolve in defaults.
+    let builder_state_alias_name = format_ident!("{}State", builder_alias_name);
+    let builder_state_alias_params = {
+        let params = builder_init_generic_args_with_lifetime.args.iter();
+        quote! {<#(#params,)* __S>}
+    };
+    let builder_state_alias_args = builder_init_generics
+        .to_generic_args()
+        .insert(0, Type::parse("__S"))
+        .with_implicit_lifetime(ir.implicit_lifetime);
+    let builder_init_generic_args_with_state_with_lifetime = builder_init_generics
+        .to_generic_args()
+        .insert(0, ir.builder_state_type_initial())
+        .with_implicit_lifetime(ir.implicit_lifetime);
+
+    let delegate_name = &ir.delegate_name;
+    let delegate_args = ir.delegate_args();
+    let exit_params = ir.exit_params();
+    let builder_name = &ir.builder_name;
+    let builder_return_type = &ir.builder_return_type;
+    let builder_entry = &ir.builder_entry;
+    let builder_exit = &ir.builder_exit;
+    let builder_state_initial = ir.builder_state_initial();
+
+    let async_token = ir.is_async.then(|| quote! {async});
+    let await_token = ir.is_async.then(|| quote! {.await});
+    let vis = &ir.vis;
+    let builder_vis = &ir.builder_vis;
+    let receiver = &ir.receiver;
+    let reference = &ir
+        .receiver
+        .as_ref()
+        .map(|r| r.reference.as_ref().map(|_| quote! { & }));
+    let mutability = ir.receiver.as_ref().map(|r| r.mutability);
+    let builder_receiver = ir.receiver.as_ref().map(|_| quote! { self });
+    let builder_receiver_param = ir
+        .receiver
+        .as_ref()
+        .map(|_| quote! { receiver: #reference #mutability #target_name #ty_generics });
+    let builder_receiver_field_definition = ir
+        .receiver
+        .as_ref()
+        .map(|_| quote! { receiver: #reference #builder_lifetime #mutability #target_name #ty_generics, });
+    let builder_receiver_field = ir.receiver.as_ref().map(|_| quote! { receiver, });
+    let builder_receiver_call = ir
+        .receiver
+        .as_ref()
+        .map(|_| quote! { self.receiver. })
+        .unwrap_or_else(|| quote! {#target_name::});
+    let builder_receiver_move = ir
+        .receiver
+        .as_ref()
+        .map(|_| quote! { receiver: self.receiver, });
+
+    let (builder_methods, setter_traits): (Vec<_>, Vec<_>) = builder_methods(
+        &ir,
+        builder_where_clause,
+        builder_receiver_move,
+        &builder_lifetime_generics,
+        &builder_alias_name,
+    )?
+    .into_iter()
+    .unzip();
+
+    let exit_where_clause = with_predicates(builder_where_clause, ir.param_predicates());
+    let field_traits = field_traits(&ir);
+    let group_traits = group_traits(&ir);
+    let (to_builder, existing) = to_builder(&ir, &builder_init_generics, &builder_state_alias_name);
+    let dynamic_builder = dynamic_builder(
+        &ir,
+        &builder_init_generics,
+        &builder_init_generic_args_phantom,
+    );
+
+    let blocking_exit = blocking_exit(&ir, &exit_where_clause);
+    let exits = exits(&ir, &exit_where_clause);
+    let conversions = conversions(
+        &ir,
+        &builder_generics,
+        quote!(#builder_name #builder_tuple_ty_generics),
+        &exit_where_clause,
+    );
+
+    let doc = ir.doc;
+
+    let type_doc = "Autogenerated by buildstructor";
+    let state_type_doc = format!(
+        "`{}` in state `__S`, see [`buildstructor::state`](https://docs.rs/buildstructor/latest/buildstructor/state/index.html)",
+        builder_alias_name
+    );
+
+    Ok(quote! {
+        impl #impl_generics #self_ty #where_clause {
+            #(#doc)*
+            #[must_use]
+            #vis fn #builder_entry #method_generics(#receiver) -> #builder_alias_name #builder_entry_generic_args {
+                #module_name::new(#builder_receiver)
+            }
+
+            #to_builder
+        }
+
+        #dynamic_builder
+
+        #[doc=#type_doc]
+        #[allow(type_alias_bounds)]
+        #vis type #builder_alias_name #builder_init_generic_args_with_lifetime = #module_name::#builder_name #builder_init_generic_args_with_state_with_lifetime;
+
+        #[doc=#state_type_doc]
+        #[allow(type_alias_bounds)]
+        #vis type #builder_state_alias_name #builder_state_alias_params = #module_name::#builder_name #builder_state_alias_args;
+
+        #(#setter_traits)*
+
+        mod #module_name {
+            use super::*;
+
+            #[inline(always)]
+            #[must_use]
+            #builder_vis fn new #builder_init_generics(#builder_receiver_param) -> #builder_name #builder_init_generic_args_with_state
+            {
+                #builder_name {
+                    #builder_receiver_field
+                    fields: (#(#builder_state_initial ,) *),
+                    _phantom: core::default::Default::default()
+                }
+            }
+
+            #[allow(unused_imports)]
+            use #krate::state::{
+                Discard as __Discard, Existing as __Existing, GroupExisting as __GroupExisting, GroupSet as __GroupSet,
+                GroupUnset as __GroupUnset, Optional as __Optional, Required as __Required,
+                Set as __Set,
+            };
+
+            #existing
+
+
+            #builder_vis struct #builder_name #all_ty_generics {
+                #builder_receiver_field_definition
+                fields: __P,
+                _phantom: core::marker::PhantomData<(#builder_init_generic_args_phantom)>
+            }
+
+            #(#builder_methods)*
+
+            #(#field_traits)*
+
+            #group_traits
+
+            impl #builder_impl_generics #builder_name #builder_tuple_ty_generics {
+                #[inline(always)]
+                #builder_vis #async_token fn #builder_exit(self, #(#exit_params),*) #builder_return_type #exit_where_clause {
+                    #builder_receiver_call #delegate_name(#(#delegate_args),*) #await_token
+                }
+
+                #blocking_exit
+
+                #(#exits)*
+            }
+
+            #conversions
+        }
+    })
+}
+
+pub fn builder_methods(
+    ir: &Ir,
+    builder_where_clause: Option<&WhereClause>,
+    builder_receiver_move: Option<TokenStream>,
+    builder_lifetime_generics: &Generics,
+    builder_alias_name: &Ident,
+) -> Result<Vec<(TokenStream, TokenStream)>> {
+    let builder_generics = Generics::combine(vec![
+        builder_lifetime_generics,
+        &ir.impl_generics,
+        &ir.delegate_generics,
+    ]);
+    let builder_vis = &ir.builder_vis;
+    let vis = &ir.vis;
+    let krate = &ir.krate;
+    let (trait_generics, _, _) = builder_generics.split_for_impl();
+    let trait_args = builder_generics.to_generic_args().maybe();
+
+    Ok(ir.builder_fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let builder_name = &ir.builder_name;
+            let field_name = &f.name;
+            let method_name = f.name.clone();
+            let ty = &f.ty;
+            let some = f.collection_some();
+            let builder_type_generics = ir.builder_type_generics();
+            // Setters that change state are available in every state, the where clause on the setter decides if it may be called.
+            // This allows us to give a helpful error message rather than 'method not found'.
+            let changes_state = matches!(f.field_type, FieldType::Regular | FieldType::Option);
+            let params_before = if changes_state {
+                builder_type_generics.to_tuple_type()
+            } else {
+                builder_type_generics
+                    .to_tuple_type()
+                    .with_type(idx, f.ty.clone().wrap_in_generic(format_ident!("__Optional")))
+            };
+            let state_param = format_ident!("__{}", idx);
+            let settable = f.settable_trait();
+            let mut setter_predicates: Vec<WherePredicate> = vec![parse_quote!(#state_param: #settable<#ty>)];
+            // Setting a member of a group also moves the group to the set state.
+            let group_slot = f.group.map(|g| ir.group_slot(g));
+            // The other members of an exclusive group are discarded if they were copied by `to_builder()`.
+            let mut discarded: Vec<(usize, Type, Expr)> = Vec::new();
+            if let Some(g) = f.group {
+                let group = &ir.groups[g];
+                if group.exclusive {
+                    let group_param = format_ident!("__{}", ir.group_slot(g));
+                    let group_settable = group.settable_trait();
+                    setter_predicates.push(parse_quote!(#group_param: #group_settable));
+                    for (member_idx, _) in ir.builder_fields.iter().enumerate().filter(|(member_idx, member)| *member_idx != idx && member.group == Some(g)) {
+                        let member_param = format_ident!("__{}", member_idx);
+                        let member_field = ir.tuple_field(member_idx);
+                        setter_predicates.push(parse_quote!(#member_param: __Discard<#group_param>));
+                        discarded.push((
+                            member_idx,
+                            parse_quote!(<#member_param as __Discard<#group_param>>::Output),
+                            parse_quote!(<#member_param as __Discard<#group_param>>::discard(#member_field)),
+                        ));
+                    }
+                }
+            }
+            let setter_where_clause = with_predicates(builder_where_clause, setter_predicates);
+            let params_after = match f.field_type {
+                FieldType::Regular | FieldType::Option => {
+                    let mut params_after = builder_type_generics
+                        .to_tuple_type()
+                        .with_type(idx, f.ty.clone().wrap_in_generic(format_ident!("__Set")));
+                    for (member_idx, member_ty, _) in &discarded {
+                        params_after = params_after.with_type(*member_idx, member_ty.clone());
+                    }
+                    match group_slot {
+                        Some(slot) => params_after.with_type(slot, Type::parse("__GroupSet")),
+                        None => params_after,
+                    }
+                }
+                _ => builder_type_generics
+                    .to_tuple_type()
+                    .with_type(idx, f.ty.clone().wrap_in_generic(format_ident!("__Optional"))),
+            };
+            let before = builder_generics
+                .to_generic_args()
+                .insert(0, Type::Tuple(params_before));
+            let after = builder_generics
+                .to_generic_args()
+                .insert(0, Type::Tuple(params_after));
+
+            let set: Expr = parse_quote!(__Set::new(#field_name));
+            let new_state = params(ir, idx, field_name, &builder_type_generics, set, group_slot, &discarded);
+            let output = if changes_state { &after } else { &before };
+            let builder_type_generics = if changes_state {
+                Generics::combine(vec![&builder_type_generics, &builder_generics])
+            } else {
+                Generics::combine(vec![&builder_type_generics.without(idx), &builder_generics])
+            };
+
+            let (mut methods, mut signatures, mut calls) = match f.field_type {
+                FieldType::Option => {
+                    let and_method_name = format_ident!("and_{}", f.name);
+                    let mut field_collection_type = f.generic_types.generic_type.clone();
+                    let mut into_generics = None;
+                    let mut into_call = None;
+                    if f.generic_types.generic_into {
+                        let into_type = field_collection_type.replace(Type::parse("__T"));
+                        let _ = into_generics.insert(Some(quote! {
+                            <__T: Into<#into_type>>
+                        }));
+                        into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+                    let mut signatures = vec![
+                        quote!(fn #method_name #into_generics(self, #field_name: #field_collection_type) -> Self::Output),
+                    ];
+                    let mut calls = vec![
+                        quote!(self.#method_name(#field_name)),
+                    ];
+                    // Passing `None` to a member of a group that needs a member would leave the group empty, so there is no `and_` setter.
+                    let and_method = (!f.group.is_some_and(|g| ir.groups[g].needs_member())).then(|| {
+                        signatures.push(quote!(fn #and_method_name #into_generics(self, #field_name: Option<#field_collection_type>) -> Self::Output));
+                        calls.push(quote!(self.#and_method_name(#field_name)));
+                        quote! {
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #and_method_name #into_generics(self, #field_name: Option<#field_collection_type>) -> #builder_name #after #setter_where_clause {
+                                let #field_name = #field_name.map(|v|v #into_call);
+                                #builder_name {
+                                    #builder_receiver_move
+                                    fields: #new_state,
+                                    _phantom: core::default::Default::default()
+                                }
+                            }
+                        }
+                    });
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #method_name #into_generics(self, #field_name: #field_collection_type) -> #builder_name #after #setter_where_clause {
+                                let #field_name = Some(#field_name #into_call);
+                                #builder_name {
+                                    #builder_receiver_move
+                                    fields: #new_state,
+                                    _phantom: core::default::Default::default()
+                                }
+                            }
+                            #and_method
+                        }
+                    }, signatures, calls)
+                },
+                FieldType::Set => {
+                    let (singular, plural) = single_plural_names(field_name, f.singular.as_ref());
+                    let insert = &f.insert;
+                    let mut field_collection_type = f.generic_types.generic_type.clone();
+                    let mut into_generics = None;
+                    let mut into_call = None;
+                    if f.generic_types.generic_into {
+                        let into_type = field_collection_type.replace(Type::parse("__T"));
+                        let _ = into_generics.insert(Some(quote! {
+                            <__T: Into<#into_type>>
+                        }));
+                        into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+                    let (plural_into, plural_into_call, plural_item_type) = into_parts(
+                        f.generic_types.generic_into,
+                        f.generic_types.generic_type.clone(),
+                        "__T",
+                    );
+                    let plural_into = plural_into.iter();
+                    let plural_generics = quote!(<__C: IntoIterator<Item = #plural_item_type> #(, #plural_into)*>);
+                    let index = Index::from(idx);
+                    let signatures = vec![
+                        quote!(fn #plural #plural_generics(self, #field_name: __C) -> Self::Output),
+                        quote!(fn #singular #into_generics(self, value: #field_collection_type) -> Self::Output),
+                    ];
+                    let calls = vec![
+                        quote!(self.#plural(#field_name)),
+                        quote!(self.#singular(value)),
+                    ];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #plural #plural_generics(mut self, #field_name: __C) -> #builder_name #before #builder_where_clause {
+                                let collection = self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some;
+                                for value in #field_name {
+                                    collection.#insert(value #plural_into_call);
+                                }
+                                self
+                            }
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #singular #into_generics(mut self, value: #field_collection_type) -> #builder_name #before #builder_where_clause{
+                                self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some.#insert(value #into_call);
+                                self
+                            }
+
+                        }
+                    }, signatures, calls)
+                },
+                FieldType::Vec => {
+                    let (singular, plural) = single_plural_names(field_name, f.singular.as_ref());
+                    let insert = &f.insert;
+                    let mut field_collection_type = f.generic_types.generic_type.clone();
+                    let mut into_generics = None;
+                    let mut into_call = None;
+                    if f.generic_types.generic_into {
+                        let into_type = field_collection_type.replace(Type::parse("__T"));
+                        let _ = into_generics.insert(Some(quote! {
+                            <__T: Into<#into_type>>
+                        }));
+                        into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+                    let (plural_into, plural_into_call, plural_item_type) = into_parts(
+                        f.generic_types.generic_into,
+                        f.generic_types.generic_type.clone(),
+                        "__T",
+                    );
+                    let plural_into = plural_into.iter();
+                    let plural_generics = quote!(<__C: IntoIterator<Item = #plural_item_type> #(, #plural_into)*>);
+                    let index = Index::from(idx);
+
+                    let signatures = vec![
+                        quote!(fn #plural #plural_generics(self, #field_name: __C) -> Self::Output),
+                        quote!(fn #singular #into_generics(self, value: #field_collection_type) -> Self::Output),
+                    ];
+                    let calls = vec![
+                        quote!(self.#plural(#field_name)),
+                        quote!(self.#singular(value)),
+                    ];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #plural #plural_generics(mut self, #field_name: __C) -> #builder_name #before #builder_where_clause {
+                                let collection = self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some;
+                                for value in #field_name {
+                                    collection.#insert(value #plural_into_call);
+                                }
+                                self
+                            }
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #singular #into_generics(mut self, value: #field_collection_type) -> #builder_name #before #builder_where_clause{
+                                self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some.#insert(value #into_call);
+                                self
+                            }
+
+                        }
+                    }, signatures, calls)
+                },
+                FieldType::Map => {
+                    let (singular, plural) = single_plural_names(field_name, f.singular.as_ref());
+                    let insert = &f.insert;
+                    let mut field_key_type = f.generic_types.key_type.clone();
+                    let mut field_value_type = f.generic_types.value_type.clone();
+                    let mut into_generics = Vec::new();
+                    let mut field_key_into_call = None;
+                    let mut field_value_into_call = None;
+                    if f.generic_types.key_into {
+                        let into_type = field_key_type.replace(Type::parse("__K"));
+                        into_generics.push(quote! {
+                            __K: Into<#into_type>
+                        });
+                        field_key_into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+                    if f.generic_types.value_into {
+                        let into_type = field_value_type.replace(Type::parse("__V"));
+                        into_generics.push(quote! {
+                            __V: Into<#into_type>
+                        });
+                        field_value_into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+
+                    let into_generics_final = if into_generics.is_empty() {
+                        None
+                    }
+                    else {
+                        Some(quote! {
+                            <#(#into_generics),*>
+                        })
+                    };
+
+
+                    let (plural_key_into, plural_key_into_call, plural_key_type) = into_parts(
+                        f.generic_types.key_into,
+                        f.generic_types.key_type.clone(),
+                        "__K",
+                    );
+                    let (plural_value_into, plural_value_into_call, plural_value_type) = into_parts(
+                        f.generic_types.value_into,
+                        f.generic_types.value_type.clone(),
+                        "__V",
+                    );
+                    let plural_into = plural_key_into.iter().chain(plural_value_into.iter());
+                    let plural_generics = quote!(<__C: IntoIterator<Item = (#plural_key_type, #plural_value_type)> #(, #plural_into)*>);
+                    let index = Index::from(idx);
+                    let signatures = vec![
+                        quote!(fn #plural #plural_generics(self, #field_name: __C) -> Self::Output),
+                        quote!(fn #singular #into_generics_final(self, key: #field_key_type, value: #field_value_type) -> Self::Output),
+                    ];
+                    let calls = vec![
+                        quote!(self.#plural(#field_name)),
+                        quote!(self.#singular(key, value)),
+                    ];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #plural #plural_generics(mut self, #field_name: __C) -> #builder_name #before #builder_where_clause {
+                                let collection = self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some;
+                                for (key, value) in #field_name {
+                                    collection.#insert(key #plural_key_into_call, value #plural_value_into_call);
+                                }
+                                self
+                            }
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #singular #into_generics_final (mut self, key: #field_key_type, value: #field_value_type) -> #builder_name #before {
+                                self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some.#insert(key #field_key_into_call, value #field_value_into_call);
+                                self
+                            }
+                        }
+                    }, signatures, calls)
+                },
+                FieldType::Collection => {
+                    let collection_ty = f.collection_ty();
+                    let (singular, plural) = single_plural_names(field_name, f.singular.as_ref());
+                    let index = Index::from(idx);
+                    let plural_predicate: WherePredicate = parse_quote!(#collection_ty: #krate::Collection<__C::Item>);
+                    let singular_predicate: WherePredicate = parse_quote!(#collection_ty: #krate::Collection<__T>);
+                    let plural_where_clause = with_predicates(builder_where_clause, vec![plural_predicate.clone()]);
+                    let singular_where_clause = with_predicates(builder_where_clause, vec![singular_predicate.clone()]);
+                    let signatures = vec![
+                        quote!(fn #plural<__C: IntoIterator>(self, #field_name: __C) -> Self::Output where #plural_predicate),
+                        quote!(fn #singular<__T>(self, value: __T) -> Self::Output where #singular_predicate),
+                    ];
+                    let calls = vec![
+                        quote!(self.#plural(#field_name)),
+                        quote!(self.#singular(value)),
+                    ];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #plural<__C: IntoIterator>(mut self, #field_name: __C) -> #builder_name #before #plural_where_clause {
+                                let collection = self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some;
+                                for item in #field_name {
+                                    #krate::Collection::insert_item(collection, item);
+                                }
+                                self
+                            }
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #singular<__T>(mut self, value: __T) -> #builder_name #before #singular_where_clause {
+                                #krate::Collection::insert_item(self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some, value);
+                                self
+                            }
+
+                        }
+                    }, signatures, calls)
+                },
+                FieldType::MapCollection => {
+                    let collection_ty = f.collection_ty();
+                    let (singular, plural) = single_plural_names(field_name, f.singular.as_ref());
+                    let index = Index::from(idx);
+                    let predicate: WherePredicate = parse_quote!(#collection_ty: #krate::MapCollection<__K, __V>);
+                    let where_clause = with_predicates(builder_where_clause, vec![predicate.clone()]);
+                    let signatures = vec![
+                        quote!(fn #plural<__C: IntoIterator<Item = (__K, __V)>, __K, __V>(self, #field_name: __C) -> Self::Output where #predicate),
+                        quote!(fn #singular<__K, __V>(self, key: __K, value: __V) -> Self::Output where #predicate),
+                    ];
+                    let calls = vec![
+                        quote!(self.#plural(#field_name)),
+                        quote!(self.#singular(key, value)),
+                    ];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #plural<__C: IntoIterator<Item = (__K, __V)>, __K, __V>(mut self, #field_name: __C) -> #builder_name #before #where_clause {
+                                let collection = self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some;
+                                for (key, value) in #field_name {
+                                    #krate::MapCollection::insert_entry(collection, key, value);
+                                }
+                                self
+                            }
+
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #singular<__K, __V>(mut self, key: __K, value: __V) -> #builder_name #before #where_clause {
+                                #krate::MapCollection::insert_entry(self.fields.#index.lazy_mut().get_or_insert_with(||core::default::Default::default()) #some, key, value);
+                                self
+                            }
+                        }
+                    }, signatures, calls)
+                },
+                _ => {
+                    let mut into_generics = None;
+                    let mut into_call = None;
+                    let mut ty = Some(ty.clone());
+                    if f.ty_into {
+                        let into_type = ty.replace(Type::parse("__T"));
+                        let _ = into_generics.insert(Some(quote! {
+                            <__T: Into<#into_type>>
+                        }));
+                        into_call = Some(quote!{
+                            .into()
+                        })
+                    }
+                    let signatures = vec![quote!(fn #method_name #into_generics(self, #field_name: #ty) -> Self::Output)];
+                    let calls = vec![quote!(self.#method_name(#field_name))];
+                    (quote! {
+                        impl #builder_type_generics #builder_name #before {
+                            #[inline(always)]
+                            #[must_use]
+                            #builder_vis fn #method_name #into_generics(self, #field_name: #ty) -> #builder_name #after #setter_where_clause {
+                                let #field_name = #field_name #into_call;
+                                #builder_name {
+                                    #builder_receiver_move
+                                    fields: #new_state,
+                                    _phantom: core::default::Default::default()
+                                }
+                            }
+                        }
+                    }, signatures, calls)
+                },
+            };
+
+            // Optional collections may also be replaced as a whole, so that `None` can be passed through.
+            if let Some(collection_ty) = &f.optional_collection {
+                let and_method_name = format_ident!("and_{}", f.name);
+                let index = Index::from(idx);
+                signatures.push(quote!(fn #and_method_name(self, #field_name: Option<#collection_ty>) -> Self::Output));
+                calls.push(quote!(self.#and_method_name(#field_name)));
+                methods = quote! {
+                    #methods
+
+                    impl #builder_type_generics #builder_name #before {
+                        #[inline(always)]
+                        #[must_use]
+                        #builder_vis fn #and_method_name(mut self, #field_name: Option<#collection_ty>) -> #builder_name #before #builder_where_clause {
+                            *self.fields.#index.lazy_mut() = Some(#field_name);
+                            self
+                        }
+                    }
+                };
+            }
+
+            // A trait per field lets generic code call the setters on any state in which they are available.
+            let setter_trait = format_ident!("{}Sets{}", builder_alias_name, f.name.unraw().to_string().to_pascal_case());
+            let doc = format!(
+                "Setters for `{}` on `{}`, implemented for every state in which they may be called",
+                f.name.unraw(),
+                builder_alias_name
+            );
+            let output_doc = "The builder once the setter has been called";
+            let method_doc = "Calls the setter of the same name on the builder";
+            let trait_where_clause = if changes_state { &setter_where_clause } else { &builder_where_clause.cloned() };
+            let setter_trait_def = quote! {
+                #[doc=#doc]
+                #vis trait #setter_trait #trait_generics #builder_where_clause {
+                    #[doc=#output_doc]
+                    type Output;
+                    #(
+                        #[doc=#method_doc]
+                        #[must_use]
+                        #signatures;
+                    )*
+                }
+            };
+            let setter_trait_impl = quote! {
+                #methods
+
+                impl #builder_type_generics #setter_trait #trait_args for #builder_name #before #trait_where_clause {
+                    type Output = #builder_name #output;
+                    #(
+                        #[inline(always)]
+                        #signatures {
+                            #calls
+                        }
+                    )*
+                }
+            };
+            (setter_trait_impl, setter_trait_def)
+        })
+        .collect())
+}
+
+// Marker traits that describe which states a field may be set in, and which states allow the builder to complete.
+// Each trait is specific to a field so that the compiler can tell the user exactly which field is the problem.
+fn field_traits(ir: &Ir) -> Vec<TokenStream> {
+    let builder_vis = &ir.builder_vis;
+    let builder = format!("{}::{}()", ir.impl_name, ir.builder_entry);
+    let exit = &ir.builder_exit;
+    ir.builder_fields
+        .iter()
+        .map(|f| {
+            let provided = f.provided_trait();
+            let provided_message = format!("required field `{}` was not set on `{}`", f.name.unraw(), builder);
+            let provided_label = format!("call `.{}(..)` before `.{}()`", f.name.unraw(), exit);
+            let settable = f.settable_trait();
+            let settable_message = format!("field `{}` was already set on `{}`", f.name.unraw(), builder);
+            let settable_label = format!("`{}` can only be set once", f.name.unraw());
+            let provided_impls = match (&f.field_type, &f.default) {
+                (_, Some(_)) => quote! {
+                    #builder_vis trait #provided<T>: Into<__Optional<T>> {}
+                    impl<T> #provided<T> for __Set<T> {}
+                    impl<T> #provided<T> for __Optional<T> {}
+                },
+                (FieldType::Regular, None) => quote! {
+                    #builder_vis trait #provided<T>: Into<__Set<T>> {}
+                    impl<T> #provided<T> for __Set<T> {}
+                },
+                _ => quote! {
+                    #builder_vis trait #provided<T>: Into<__Set<T>> {}
+                    impl<T> #provided<T> for __Set<T> {}
+                    impl<T: Default> #provided<T> for __Optional<T> {}
+                },
+            };
+            let settable_impls = if f.is_required() {
+                quote! {
+                    impl<T> #settable<T> for __Required<T> {}
+                }
+            } else {
+                quote! {
+                    impl<T> #settable<T> for __Optional<T> {}
+                }
+            };
+            // Overridable fields may be set again, the new value replaces the old one.
+            let overridable_impls = f.overridable.then(|| {
+                quote! {
+                    impl<T> #settable<T> for __Set<T> {}
+                }
+            });
+            let existing_impls = (ir.to_builder.is_some() && !f.is_collection()).then(|| {
+                quote! {
+                    impl<T> #provided<T> for __Existing<T> {}
+                    impl<T> #settable<T> for __Existing<T> {}
+                }
+            });
+            quote! {
+                #[diagnostic::on_unimplemented(message = #provided_message, label = #provided_label)]
+                #provided_impls
+
+                #[diagnostic::on_unimplemented(message = #settable_message, label = #settable_label)]
+                #builder_vis trait #settable<T> {}
+                #settable_impls
+                #overridable_impls
+                #existing_impls
+            }
+        })
+        .collect()
+}
+
+// `to_builder` destructures an existing value and hands each field to the builder.
+// Values that came from an existing instance may be overridden, so they get their own state rather than `__Set`.
+fn to_builder(
+    ir: &Ir,
+    builder_init_generics: &Generics,
+    builder_state_alias_name: &Ident,
+) -> (Option<TokenStream>, Option<TokenStream>) {
+    let to_builder = match &ir.to_builder {
+        Some(to_builder) => to_builder,
+        None => return (None, None),
+    };
+    let vis = &ir.vis;
+    let builder_vis = &ir.builder_vis;
+    let module_name = &ir.module_name;
+    let builder_name = &ir.builder_name;
+    let members: Vec<_> = ir.builder_fields.iter().map(|f| &f.member).collect();
+    let tys: Vec<_> = ir.builder_fields.iter().map(|f| &f.ty).collect();
+    let values: Vec<_> = (0..ir.builder_fields.len())
+        .map(|idx| format_ident!("__f{}", idx))
+        .collect();
+    let builder_state_existing = ir.builder_state_existing();
+    let existing_generic_args = builder_init_generics
+        .to_generic_args()
+        .insert(0, ir.builder_state_type_existing());
+    let alias_generic_args = {
+        let args = builder_init_generics.to_generic_args().args.into_iter();
+        let state = ir.builder_state_type_existing();
+        quote! {<#(#args,)* #state>}
+    };
+    let doc = format!(
+        "Create a builder from an existing `{}`, each field may be overridden before calling `{}()`",
+        ir.impl_name, ir.builder_exit
+    );
+
+    (
+        Some(quote! {
+            #[doc=#doc]
+            #[must_use]
+            #vis fn #to_builder(self) -> #builder_state_alias_name #alias_generic_args {
+                let Self { #(#members: #values,)* .. } = self;
+                #module_name::existing(#(#values),*)
+            }
+        }),
+        Some(quote! {
+            #[inline(always)]
+            #[must_use]
+            #builder_vis fn existing #builder_init_generics(#(#values: #tys),*) -> #builder_name #existing_generic_args {
+                #builder_name {
+                    fields: (#(#builder_state_existing ,) *),
+                    _phantom: core::default::Default::default()
+                }
+            }
+        }),
+    )
+}
+
+// A builder that is checked at runtime rather than by the type system, so that it has the same type whatever has been set.
+// Every field is stored as an `Option` and `build()` reports the required fields that are missing.
+fn dynamic_builder(
+    ir: &Ir,
+    builder_init_generics: &Generics,
+    phantom: &Option<Punctuated<GenericArgument, Token![,]>>,
+) -> Option<TokenStream> {
+    if !ir.dynamic {
+        return None;
+    }
+    let vis = &ir.vis;
+    let krate = &ir.krate;
+    let target_name = &ir.impl_name;
+    let self_ty = &ir.self_ty;
+    let delegate_name = &ir.delegate_name;
+    let method_generics = &ir.delegate_generics;
+    let (impl_generics, _, where_clause) = &ir.impl_generics.split_for_impl();
+    let (dyn_impl_generics, dyn_ty_generics, dyn_where_clause) =
+        builder_init_generics.split_for_impl();
+    let dyn_name = format_ident!(
+        "{}{}DynBuilder",
+        ir.delegate_name.unraw().to_string().to_pascal_case(),
+        ir.impl_name,
+    );
+    let dyn_entry = format_ident!("dyn_{}", ir.builder_entry);
+    let exit = &ir.builder_exit;
+    let builder = format!("{}::{}()", ir.impl_name, dyn_entry);
+    let return_type = match &ir.builder_return_type {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    let async_token = ir.is_async.then(|| quote! {async});
+    let await_token = ir.is_async.then(|| quote! {.await});
+    let entry_doc = format!(
+        "Create a [`{}`], a builder that is checked when `{}()` is called rather than at compile time",
+        dyn_name, exit
+    );
+    let type_doc = format!(
+        "Autogenerated by buildstructor, see [`{}::{}`]",
+        target_name, dyn_entry
+    );
+
+    let names: Vec<&Ident> = ir.builder_fields.iter().map(|f| &f.name).collect();
+    let tys: Vec<&Type> = ir.builder_fields.iter().map(|f| &f.ty).collect();
+    let required: Vec<&Ident> = ir
+        .builder_fields
+        .iter()
+        .filter(|f| f.is_required())
+        .map(|f| &f.name)
+        .collect();
+    let required_names: Vec<String> = required.iter().map(|r| r.unraw().to_string()).collect();
+    let values: Vec<TokenStream> = ir
+        .builder_fields
+        .iter()
+        .map(|f| {
+            let name = &f.name;
+            match &f.default {
+                _ if f.is_required() => quote! { #name },
+                Some(default) => quote! { self.#name.take().unwrap_or_else(|| #default) },
+                None => quote! { self.#name.take().unwrap_or_default() },
+            }
+        })
+        .collect();
+    let delegate_args: Vec<TokenStream> = ir
+        .delegate_params
+        .iter()
+        .map(|param| match param {
+            DelegateParam::Field(idx) => values[*idx].clone(),
+            DelegateParam::Skip(skip) => quote! { #skip },
+            DelegateParam::ExitArg(idx) => {
+                let name = &ir.exit_args[*idx].name;
+                quote! { #name }
+            }
+        })
+        .collect();
+    let call = quote! {
+        Ok(#target_name::#delegate_name(#(#delegate_args),*) #await_token)
+    };
+    // Required fields are taken together so that nothing is lost if any of them are missing.
+    let body = if required.is_empty() {
+        call
+    } else {
+        quote! {
+            match (#(self.#required.take(),)*) {
+                (#(Some(#required),)*) => #call,
+                (#(#required,)*) => {
+                    let mut missing = Vec::new();
+                    #(
+                        if #required.is_none() {
+                            missing.push(#required_names);
+                        }
+                        self.#required = #required;
+                    )*
+                    Err(#krate::MissingFields::new(#builder, missing))
+                }
+            }
+        }
+    };
+    let setters = ir
+        .builder_fields
+        .iter()
+        .map(|f| dynamic_setter(vis, &ir.krate, f));
+    let exit_params = ir.exit_params();
+
+    Some(quote! {
+        impl #impl_generics #self_ty #where_clause {
+            #[doc=#entry_doc]
+            #[must_use]
+            #vis fn #dyn_entry #method_generics() -> #dyn_name #dyn_ty_generics {
+                #dyn_name {
+                    #(#names: None,)*
+                    _phantom: core::default::Default::default()
+                }
+            }
+        }
+
+        #[doc=#type_doc]
+        #vis struct #dyn_name #builder_init_generics #dyn_where_clause {
+            #(#names: Option<#tys>,)*
+            _phantom: core::marker::PhantomData<(#phantom)>
+        }
+
+        impl #dyn_impl_generics #dyn_name #dyn_ty_generics #dyn_where_clause {
+            #(#setters)*
+
+            /// Build the value, or return the names of the required fields that have not been set.
+            /// The builder is left empty if the value was built.
+            #vis #async_token fn #exit(&mut self, #(#exit_params),*) -> Result<#return_type, #krate::MissingFields> {
+                #body
+            }
+        }
+    })
+}
+
+fn dynamic_setter(vis: &Visibility, krate: &Path, f: &BuilderField) -> TokenStream {
+    let name = &f.name;
+    let ty = &f.ty;
+    let some = f.collection_some();
+    let collection_ty = f.collection_ty();
+    let (into_generic, into_call, value_ty) = match f.field_type {
+        FieldType::Regular => into_parts(f.ty_into, Some(ty.clone()), "__T"),
+        _ => into_parts(
+            f.generic_types.generic_into,
+            f.generic_types.generic_type.clone(),
+            "__T",
+        ),
+    };
+    let into_generics = into_generic.as_ref().map(|g| quote! { <#g> });
+    // The dynamic builder is public API of the user's crate, so every method is documented.
+    let field = name.unraw();
+    let set_doc = format!("Sets `{}`", field);
+    let and_doc = format!("Sets `{}` to the given `Option`", field);
+    let (singular_doc, plural_doc) = match f.field_type {
+        FieldType::Map | FieldType::MapCollection => (
+            format!("Adds an entry to `{}`", field),
+            format!("Adds every entry to `{}`", field),
+        ),
+        _ => (
+            format!("Adds an element to `{}`", field),
+            format!("Adds every element to `{}`", field),
+        ),
+    };
+    let setters = match f.field_type {
+        FieldType::Regular => quote! {
+            #[doc=#set_doc]
+            #[inline(always)]
+            #vis fn #name #into_generics(&mut self, #name: #value_ty) -> &mut Self {
+                self.#name = Some(#name #into_call);
+                self
+            }
+        },
+        FieldType::Option => {
+            let and_name = format_ident!("and_{}", name);
+            quote! {
+                #[doc=#set_doc]
+                #[inline(always)]
+                #vis fn #name #into_generics(&mut self, #name: #value_ty) -> &mut Self {
+                    self.#name = Some(Some(#name #into_call));
+                    self
+                }
+
+                #[doc=#and_doc]
+                #[inline(always)]
+                #vis fn #and_name #into_generics(&mut self, #name: Option<#value_ty>) -> &mut Self {
+                    self.#name = Some(#name.map(|v| v #into_call));
+                    self
+                }
+            }
+        }
+        FieldType::Vec | FieldType::Set => {
+            let (singular, plural) = single_plural_names(name, f.singular.as_ref());
+            let insert = &f.insert;
+            let (plural_into, plural_into_call, plural_item_ty) = into_parts(
+                f.generic_types.generic_into,
+                f.generic_types.generic_type.clone(),
+                "__T",
+            );
+            let plural_into = plural_into.iter();
+            quote! {
+                #[doc=#plural_doc]
+                #[inline(always)]
+                #vis fn #plural<__C: IntoIterator<Item = #plural_item_ty> #(, #plural_into)*>(&mut self, #name: __C) -> &mut Self {
+                    let collection = self.#name.get_or_insert_with(core::default::Default::default) #some;
+                    for value in #name {
+                        collection.#insert(value #plural_into_call);
+                    }
+                    self
+                }
+
+                #[doc=#singular_doc]
+                #[inline(always)]
+                #vis fn #singular #into_generics(&mut self, value: #value_ty) -> &mut Self {
+                    self.#name.get_or_insert_with(core::default::Default::default) #some.#insert(value #into_call);
+                    self
+                }
+            }
+        }
+        FieldType::Map => {
+            let (singular, plural) = single_plural_names(name, f.singular.as_ref());
+            let insert = &f.insert;
+            let (key_generics, key_call, key_ty) = into_parts(
+                f.generic_types.key_into,
+                f.generic_types.key_type.clone(),
+                "__K",
+            );
+            let (value_generics, value_call, value_ty) = into_parts(
+                f.generic_types.value_into,
+                f.generic_types.value_type.clone(),
+                "__V",
+            );
+            let generics_vec: Vec<_> = key_generics.into_iter().chain(value_generics).collect();
+            let generics = (!generics_vec.is_empty()).then(|| quote! { <#(#generics_vec),*> });
+            let (plural_key_generics, plural_key_call, plural_key_ty) = into_parts(
+                f.generic_types.key_into,
+                f.generic_types.key_type.clone(),
+                "__K",
+            );
+            let (plural_value_generics, plural_value_call, plural_value_ty) = into_parts(
+                f.generic_types.value_into,
+                f.generic_types.value_type.clone(),
+                "__V",
+            );
+            let plural_generics = plural_key_generics
+                .iter()
+                .chain(plural_value_generics.iter());
+            quote! {
+                #[doc=#plural_doc]
+                #[inline(always)]
+                #vis fn #plural<__C: IntoIterator<Item = (#plural_key_ty, #plural_value_ty)> #(, #plural_generics)*>(&mut self, #name: __C) -> &mut Self {
+                    let collection = self.#name.get_or_insert_with(core::default::Default::default) #some;
+                    for (key, value) in #name {
+                        collection.#insert(key #plural_key_call, value #plural_value_call);
+                    }
+                    self
+                }
+
+                #[doc=#singular_doc]
+                #[inline(always)]
+                #vis fn #singular #generics(&mut self, key: #key_ty, value: #value_ty) -> &mut Self {
+                    self.#name.get_or_insert_with(core::default::Default::default) #some.#insert(key #key_call, value #value_call);
+                    self
+                }
+            }
+        }
+        FieldType::Collection => {
+            let (singular, plural) = single_plural_names(name, f.singular.as_ref());
+            quote! {
+                #[doc=#plural_doc]
+                #[inline(always)]
+                #vis fn #plural<__C: IntoIterator>(&mut self, #name: __C) -> &mut Self where #collection_ty: #krate::Collection<__C::Item> {
+                    let collection = self.#name.get_or_insert_with(core::default::Default::default) #some;
+                    for item in #name {
+                        #krate::Collection::insert_item(collection, item);
+                    }
+                    self
+                }
+
+                #[doc=#singular_doc]
+                #[inline(always)]
+                #vis fn #singular<__T>(&mut self, value: __T) -> &mut Self where #collection_ty: #krate::Collection<__T> {
+                    #krate::Collection::insert_item(self.#name.get_or_insert_with(core::default::Default::default) #some, value);
+                    self
+                }
+            }
+        }
+        FieldType::MapCollection => {
+            let (singular, plural) = single_plural_names(name, f.singular.as_ref());
+            quote! {
+                #[doc=#plural_doc]
+                #[inline(always)]
+                #vis fn #plural<__C: IntoIterator<Item = (__K, __V)>, __K, __V>(&mut self, #name: __C) -> &mut Self where #collection_ty: #krate::MapCollection<__K, __V> {
+                    let collection = self.#name.get_or_insert_with(core::default::Default::default) #some;
+                    for (key, value) in #name {
+                        #krate::MapCollection::insert_entry(collection, key, value);
+                    }
+                    self
+                }
+
+                #[doc=#singular_doc]
+                #[inline(always)]
+                #vis fn #singular<__K, __V>(&mut self, key: __K, value: __V) -> &mut Self where #collection_ty: #krate::MapCollection<__K, __V> {
+                    #krate::MapCollection::insert_entry(self.#name.get_or_insert_with(core::default::Default::default) #some, key, value);
+                    self
+                }
+            }
+        }
+    };
+    let and_setter = f.optional_collection.as_ref().map(|collection_ty| {
+        let and_name = format_ident!("and_{}", name);
+        quote! {
+            #[doc=#and_doc]
+            #[inline(always)]
+            #vis fn #and_name(&mut self, #name: Option<#collection_ty>) -> &mut Self {
+                self.#name = Some(#name);
+                self
+            }
+        }
+    });
+    quote! {
+        #setters
+        #and_setter
+    }
+}
+
+// Setters accept anything that converts into the value type when `into` applies to the field.
+fn into_parts(
+    into: bool,
+    ty: Option<Type>,
+    param: &'static str,
+) -> (Option<TokenStream>, Option<TokenStream>, Option<Type>) {
+    if into {
+        let param = Type::parse(param);
+        (
+            Some(quote! { #param: Into<#ty> }),
+            Some(quote! { .into() }),
+            Some(param),
+        )
+    } else {
+        (None, None, ty)
+    }
+}
+
+// Sync code can drive an async builder on the minimal executor in `buildstructor`, or on a runtime of its choosing.
+fn blocking_exit(ir: &Ir, where_clause: &Option<WhereClause>) -> Option<TokenStream> {
+    let blocking_exit = ir.blocking_exit.as_ref()?;
+    let blocking_exit_on = format_ident!("{}_on", blocking_exit);
+    let krate = &ir.krate;
+    let builder_vis = &ir.builder_vis;
+    let builder_exit = &ir.builder_exit;
+    let builder_return_type = &ir.builder_return_type;
+    let exit_params = ir.exit_params();
+    let exit_args = ir.exit_arg_names();
+    Some(quote! {
+        #[inline(always)]
+        #builder_vis fn #blocking_exit(self, #(#exit_params),*) #builder_return_type #where_clause {
+            #krate::block_on(self.#builder_exit(#(#exit_args),*))
+        }
+
+        #[inline(always)]
+        #builder_vis fn #blocking_exit_on<__E: #krate::BlockOn>(self, executor: &__E, #(#exit_params),*) #builder_return_type #where_clause {
+            executor.block_on(self.#builder_exit(#(#exit_args),*))
+        }
+    })
+}
+
+// A complete builder can be finished generically through `buildstructor::Build`.
+// Constructors also convert into their target, via `TryFrom` if they return `Result<Self, E>`.
+// Async builders instead implement `IntoFuture` so that they can be awaited directly.
+// None of these can supply exit args, so builders that take them only have their exits.
+fn conversions(
+    ir: &Ir,
+    builder_generics: &Generics,
+    builder: TokenStream,
+    where_clause: &Option<WhereClause>,
+) -> Option<TokenStream> {
+    if !ir.exit_args.is_empty() {
+        return None;
+    }
+    let (impl_generics, _, _) = builder_generics.split_for_impl();
+    let impl_generics = quote!(impl #impl_generics);
+    let exit = &ir.builder_exit;
+    let krate = &ir.krate;
+    let output = match &ir.builder_return_type {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    if ir.is_async {
+        return ir
+            .into_future
+            .then(|| into_future(ir, builder_generics, builder, where_clause, output));
+    }
+    let self_ty = &ir.self_ty;
+    let conversion = match &ir.builder_return_type {
+        _ if ir.receiver.is_some() => None,
+        ReturnType::Type(_, ty) if ty == self_ty => Some(quote! {
+            #impl_generics core::convert::From<#builder> for #self_ty #where_clause {
+                #[inline(always)]
+                fn from(builder: #builder) -> Self {
+                    builder.#exit()
+                }
+            }
+        }),
+        ReturnType::Type(_, ty) => fallible_error(ty, self_ty).map(|error| {
+            quote! {
+                #impl_generics core::convert::TryFrom<#builder> for #self_ty #where_clause {
+                    type Error = #error;
+                    #[inline(always)]
+                    fn try_from(builder: #builder) -> Result<Self, Self::Error> {
+                        builder.#exit()
+                    }
+                }
+            }
+        }),
+        ReturnType::Default => None,
+    };
+    Some(quote! {
+        #impl_generics #krate::Build for #builder #where_clause {
+            type Output = #output;
+            #[inline(always)]
+            fn build(self) -> Self::Output {
+                self.#exit()
+            }
+        }
+
+        #conversion
+    })
+}
+
+// The future is boxed so that it can be named, it may borrow for the first lifetime of the builder which all other generics must outlive.
+fn into_future(
+    ir: &Ir,
+    builder_generics: &Generics,
+    builder: TokenStream,
+    where_clause: &Option<WhereClause>,
+    output: TokenStream,
+) -> TokenStream {
+    let (impl_generics, _, _) = builder_generics.split_for_impl();
+    let exit = &ir.builder_exit;
+    // A borrowed receiver is only `Send` if it is `Sync`.
+    let sync = ir
+        .receiver
+        .as_ref()
+        .and_then(|r| r.reference.as_ref())
+        .map(|_| quote!(core::marker::Sync+));
+    let mut lifetimes = builder_generics.lifetimes().map(|l| &l.lifetime);
+    let lifetime = lifetimes
+        .next()
+        .cloned()
+        .unwrap_or_else(|| Lifetime::new("'static", Span::call_site()));
+    let predicates = lifetimes
+        .map(|l| parse_quote!(#l: #lifetime))
+        .chain(builder_generics.type_params().map(|t| {
+            let t = &t.ident;
+            parse_quote!(#t: core::marker::Send + #sync #lifetime)
+        }))
+        .collect();
+    let where_clause = with_predicates(where_clause.as_ref(), predicates);
+    quote! {
+        impl #impl_generics core::future::IntoFuture for #builder #where_clause {
+            type Output = #output;
+            type IntoFuture = core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = #output> + core::marker::Send + #lifetime>>;
+            #[inline(always)]
+            fn into_future(self) -> Self::IntoFuture {
+                std::boxed::Box::pin(self.#exit())
+            }
+        }
+    }
+}
+
+// The error type if `ty` is `Result<Target, E>`.
+fn fallible_error<'a>(ty: &'a Type, target: &Type) -> Option<&'a Type> {
+    result_types(ty)
+        .filter(|(ok, _)| *ok == target)
+        .map(|(_, error)| error)
+}
+
+// Extra exits call the main exit and map its value.
+fn exits(ir: &Ir, where_clause: &Option<WhereClause>) -> Vec<TokenStream> {
+    let builder_vis = &ir.builder_vis;
+    let builder_exit = &ir.builder_exit;
+    let async_token = ir.is_async.then(|| quote! {async});
+    let await_token = ir.is_async.then(|| quote! {.await});
+    let exit_params = ir.exit_params();
+    let exit_args = ir.exit_arg_names();
+    let error = match &ir.builder_return_type {
+        ReturnType::Type(_, ty) => result_types(ty).map(|(_, error)| error),
+        ReturnType::Default => None,
+    };
+    ir.exits
+        .iter()
+        .map(|exit| {
+            let Exit { name, map, output } = exit;
+            let (output, body) = match error {
+                Some(error) => (
+                    quote! { core::result::Result<#output, #error> },
+                    quote! { self.#builder_exit(#(#exit_args),*) #await_token .map(#map) },
+                ),
+                None => (
+                    quote! { #output },
+                    quote! { (#map)(self.#builder_exit(#(#exit_args),*) #await_token) },
+                ),
+            };
+            quote! {
+                #[inline(always)]
+                #builder_vis #async_token fn #name(self, #(#exit_params),*) -> #output #where_clause {
+                    #body
+                }
+            }
+        })
+        .collect()
+}
+
+// Marker traits for the state of each group, a group is stored in a single slot after the fields.
+fn group_traits(ir: &Ir) -> Option<TokenStream> {
+    if ir.groups.is_empty() {
+        return None;
+    }
+    let builder_vis = &ir.builder_vis;
+    let builder = format!("{}::{}()", ir.impl_name, ir.builder_entry);
+    let exit = &ir.builder_exit;
+    let traits = ir.groups.iter().map(|g| {
+        let members = g
+            .members
+            .iter()
+            .map(|m| format!("`{}`", m.unraw()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let setters = g
+            .members
+            .iter()
+            .map(|m| format!("`.{}(..)`", m.unraw()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let settable = g.exclusive.then(|| {
+            let settable = g.settable_trait();
+            let message = format!("only one of {} may be set on `{}`", members, builder);
+            let label = format!("another member of group `{}` was already set", g.name);
+            // Members copied by `to_builder()` may be replaced by one new member.
+            let existing = ir.to_builder.as_ref().map(|_| {
+                quote! {
+                    impl #settable for __GroupExisting {}
+                }
+            });
+            quote! {
+                #[diagnostic::on_unimplemented(message = #message, label = #label)]
+                #builder_vis trait #settable {}
+                impl #settable for __GroupUnset {}
+                #existing
+            }
+        });
+        let provided = g.needs_member().then(|| {
+            let provided = g.provided_trait();
+            let message = format!("one of {} must be set on `{}`", members, builder);
+            let label = format!("call one of {} before `.{}()`", setters, exit);
+            let existing = ir.to_builder.as_ref().map(|_| {
+                quote! {
+                    impl #provided for __GroupExisting {}
+                }
+            });
+            quote! {
+                #[diagnostic::on_unimplemented(message = #message, label = #label)]
+                #builder_vis trait #provided {}
+                impl #provided for __GroupSet {}
+                #existing
+            }
+        });
+        quote! {
+            #settable
+            #provided
+        }
+    });
+    Some(quote! {
+        #(#traits)*
+    })
+}
+
+fn with_predicates(
+    where_clause: Option<&WhereClause>,
+    predicates: Vec<WherePredicate>,
+) -> Option<WhereClause> {
+    let predicates: Vec<WherePredicate> = where_clause
+        .into_iter()
+        .flat_map(|w| w.predicates.iter().cloned())
+        .chain(predicates)
+        .collect();
+    if predicates.is_empty() {
+        None
+    } else {
+        Some(WhereClause {
+            where_token: Default::default(),
+            predicates: Punctuated::from_iter(predicates),
+        })
+    }
+}
+
+fn single_plural_names(ident: &Ident, singular: Option<&Ident>) -> (Ident, Ident) {
+    let plural = ident.clone();
+    if let Some(singular) = singular {
+        return (singular.clone(), plural);
+    }
+    let mut singular = format_ident!("{}", ident.unraw().to_string().to_singular());
+    if plural.unraw() == singular {
+        singular = format_ident!("{}_entry", ident);
+    }
+    (singular, plural)
+}
+
+fn params(
+    ir: &Ir,
+    idx: usize,
+    field_name: &Ident,
+    builder_type_generics: &Generics,
+    set: Expr,
+    group_slot: Option<usize>,
+    discarded: &[(usize, Type, Expr)],
+) -> Expr {
+    let mut tuple = builder_type_generics
+        .to_expr_tuple(|idxp, _| {
+            if idx == idxp {
+                Expr::Path(field_name.to_expr_path())
+            } else {
+                ir.tuple_field(idxp)
+            }
+        })
+        .with_expr(idx, set);
+    for (member_idx, _, member) in discarded {
+        tuple = tuple.with_expr(*member_idx, member.clone());
+    }
+    Expr::Tuple(match group_slot {
+        Some(slot) => tuple.with_expr(slot, parse_quote!(__GroupSet)),
+        None => tuple,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyze::analyze;
+    use crate::buildstructor::tests::*;
+    use crate::codegen::codegen;
+    use crate::lower::lower;
+
+    macro_rules! assert_codegen {
+        ($input:expr) => {
+            use rust_format::Formatter;
+            let models = analyze(false, &$input).expect("Analysis failed");
+            for model in models {
+                let ir = lower(model.expect("Analysis failed")).expect("Ir failed");
+                if let Ok(codegen) = codegen(ir) {
+                    let output = rust_format::RustFmt::default()
+                        .format_str(codegen.to_string())
+                        .unwrap();
+                    insta::assert_snapshot!(output);
+                } else {
+                    panic!("Failed generate code");
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn single_field_test() {
+        assert_codegen!(single_field_test_case());
+    }
+
+    #[test]
+    fn pub_test() {
+        assert_codegen!(pub_test_case());
+    }
+
+    #[test]
+    fn multi_field_test() {
+        assert_codegen!(multi_field_test_case());
+    }
+
+    #[test]
+    fn generic_test() {
+        assert_codegen!(generic_test_case());
+    }
+
+    #[test]
+    fn async_test() {
+        assert_codegen!(async_test_case());
+    }
+
+    #[test]
+    fn into_future_test() {
+        assert_codegen!(into_future_test_case());
+    }
+
+    #[test]
+    fn async_receiver_test() {
+        assert_codegen!(async_receiver_test_case());
+    }
+
+    #[test]
+    fn blocking_exit_test() {
+        assert_codegen!(blocking_exit_test_case());
+    }
+
+    #[test]
+    fn fallible_test() {
+        assert_codegen!(fallible_test_case());
+    }
+
+    #[test]
+    fn exits_test() {
+        assert_codegen!(exits_test_case());
+    }
+
+    #[test]
+    fn into_test() {
+        assert_codegen!(into_test_case());
+    }
+
+    #[test]
+    fn into_where_test() {
+        assert_codegen!(into_where_test_case());
+    }
+
+    #[test]
+    fn option_test() {
+        assert_codegen!(option_test_case());
+    }
+
+    #[test]
+    fn collection_test() {
+        assert_codegen!(collections_test_case());
+    }
+
+    #[test]
+    fn collection_generics_test() {
+        assert_codegen!(collections_generics_test_case());
+    }
+
+    #[test]
+    fn collection_option_test() {
+        assert_codegen!(collections_option_test_case());
+    }
+
+    #[test]
+    fn returns_self_test() {
+        assert_codegen!(returns_self_test_case());
+    }
+
+    #[test]
+    fn multiple_generics_test() {
+        assert_codegen!(multiple_generics_test_case());
+    }
+
+    #[test]
+    fn collection_generics_test2() {
+        assert_codegen!(collections_generics_test_case2());
+    }
+
+    #[test]
+    fn self_receiver() {
+        assert_codegen!(self_receiver_test_case());
+    }
+
+    #[test]
+    fn doc() {
+        assert_codegen!(doc_test_case());
+    }
+
+    #[test]
+    fn reference() {
+        assert_codegen!(reference_test_case());
+    }
+
+    #[test]
+    fn self_reference() {
+        assert_codegen!(self_reference_test_case());
+    }
+
+    #[test]
+    fn lifetime() {
+        assert_codegen!(lifetime_test_case());
+    }
+
+    #[test]
+    fn specialization() {
+        assert_codegen!(specialization_test_case());
+    }
+
+    #[test]
+    fn specialization_self() {
+        assert_codegen!(specialization_returns_self_test_case());
+    }
+
+    #[test]
+    fn associated_types() {
+        assert_codegen!(associated_types_test_case());
+    }
+
+    #[test]
+    fn default_test() {
+        assert_codegen!(default_test_case());
+    }
+
+    #[test]
+    fn exit_arg_test() {
+        assert_codegen!(exit_arg_test_case());
+    }
+
+    #[test]
+    fn qualified_collections_test() {
+        assert_codegen!(qualified_collections_test_case());
+    }
+
+    #[test]
+    fn collection_override_test() {
+        assert_codegen!(collection_override_test_case());
+    }
+
+    #[test]
+    fn trait_collection_test() {
+        assert_codegen!(trait_collection_test_case());
+    }
+
+    #[test]
+    fn optional_collection_test() {
+        assert_codegen!(optional_collection_test_case());
+    }
+
+    #[test]
+    fn skip_test() {
+        assert_codegen!(skip_test_case());
+    }
+
+    #[test]
+    fn setter_test() {
+        assert_codegen!(setter_test_case());
+    }
+
+    #[test]
+    fn into_override_test() {
+        assert_codegen!(into_override_test_case());
+    }
+
+    #[test]
+    fn to_builder_test() {
+        assert_codegen!(to_builder_test_case());
+    }
+
+    #[test]
+    fn overridable_test() {
+        assert_codegen!(overridable_test_case());
+    }
+
+    #[test]
+    fn group_test() {
+        assert_codegen!(group_test_case());
+    }
+
+    #[test]
+    fn at_least_one_test() {
+        assert_codegen!(at_least_one_test_case());
+    }
+
+    #[test]
+    fn dynamic_test() {
+        assert_codegen!(dynamic_test_case());
+    }
+}
@@ -0,0 +1,1086 @@
+use crate::analyze::{BuilderModel, FieldConfig};
+use crate::buildstructor::utils::{IdentExt, PunctuatedExt, TypeExt};
+use inflector::Inflector;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use std::default::Default;
+use syn::ext::IdentExt as _;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Attribute, LitBool, TypeReference, WherePredicate};
+use syn::{
+    Expr, ExprField, FnArg, GenericArgument, GenericParam, Generics, Index, Member, Pat, Path,
+    PathArguments, Receiver, Result, ReturnType, Type, TypeParam, TypeTuple, VisRestricted,
+    Visibility,
+};
+use try_match::try_match;
+
+pub struct Ir {
+    pub module_name: Ident,
+    pub impl_name: Ident,
+    pub impl_generics: Generics,
+    pub delegate_name: Ident,
+    pub delegate_generics: Generics,
+    pub builder_name: Ident,
+    pub builder_fields: Vec<BuilderField>,
+    pub delegate_params: Vec<DelegateParam>,
+    pub exit_args: Vec<ExitArg>,
+    pub builder_return_type: ReturnType,
+    pub builder_vis: Visibility,
+    pub builder_generics: Generics,
+    pub builder_entry: Ident,
+    pub builder_exit: Ident,
+    pub vis: Visibility,
+    pub is_async: bool,
+    pub receiver: Option<Receiver>,
+    pub doc: Vec<Attribute>,
+    pub implicit_lifetime: bool,
+    pub self_ty: Box<Type>,
+    pub to_builder: Option<Ident>,
+    pub groups: Vec<Group>,
+    pub dynamic: bool,
+    pub into_future: bool,
+    pub blocking_exit: Option<Ident>,
+    pub exits: Vec<Exit>,
+    pub krate: Path,
+}
+
+pub struct BuilderField {
+    pub name: Ident,
+    pub singular: Option<Ident>,
+    pub field_type: FieldType,
+    pub ty: Type,
+    pub ty_into: bool,
+    pub generic_types: GenericTypes,
+    pub default: Option<Expr>,
+    pub member: Member,
+    pub overridable: bool,
+    pub group: Option<usize>,
+    // The method used to add a single element, only set for collections.
+    pub insert: Option<Ident>,
+    // The collection inside `Option<Vec<T>>` and friends, which stay `None` until an element is added.
+    pub optional_collection: Option<Type>,
+}
+
+impl BuilderField {
+    // A field is only required if the user must call the setter before the builder can complete.
+    pub fn is_required(&self) -> bool {
+        matches!(self.field_type, FieldType::Regular) && self.default.is_none()
+    }
+
+    pub fn is_collection(&self) -> bool {
+        self.field_type.is_collection()
+    }
+
+    // The type that elements are added to, this is inside the `Option` for optional collections.
+    pub fn collection_ty(&self) -> &Type {
+        self.optional_collection.as_ref().unwrap_or(&self.ty)
+    }
+
+    // Reaches through the `Option` of an optional collection, creating the collection if needed.
+    pub fn collection_some(&self) -> Option<TokenStream> {
+        self.optional_collection
+            .as_ref()
+            .map(|_| quote!(.get_or_insert_with(core::default::Default::default)))
+    }
+
+    // Implemented for the states that this field's setter may be called in.
+    pub fn settable_trait(&self) -> Ident {
+        format_ident!(
+            "__{}Settable",
+            self.name.unraw().to_string().to_pascal_case()
+        )
+    }
+
+    // Implemented for the states that allow the builder to complete.
+    pub fn provided_trait(&self) -> Ident {
+        format_ident!(
+            "__{}Provided",
+            self.name.unraw().to_string().to_pascal_case()
+        )
+    }
+}
+
+// A parameter that is passed to the exit rather than set on the builder.
+pub struct ExitArg {
+    pub name: Ident,
+    pub ty: Type,
+}
+
+// An additional exit that maps the value returned by the delegate, only the `Ok` value is mapped for fallible delegates.
+pub struct Exit {
+    pub name: Ident,
+    pub map: Expr,
+    pub output: Type,
+}
+
+// A set of optional fields that are constrained together. Each group has its own slot in the builder state after the fields.
+pub struct Group {
+    pub name: String,
+    pub span: Span,
+    pub members: Vec<Ident>,
+    pub exclusive: bool,
+    pub required: bool,
+    pub at_least_one: bool,
+}
+
+impl Group {
+    // The builder can only complete once a member of the group has been set.
+    pub fn needs_member(&self) -> bool {
+        self.required || self.at_least_one
+    }
+
+    // Implemented for the group states that allow another member to be set.
+    pub fn settable_trait(&self) -> Ident {
+        format_ident!("__{}GroupSettable", self.name.to_pascal_case())
+    }
+
+    // Implemented for the group states that allow the builder to complete.
+    pub fn provided_trait(&self) -> Ident {
+        format_ident!("__{}GroupProvided", self.name.to_pascal_case())
+    }
+}
+
+// Where the value of each delegate parameter comes from when the builder completes.
+pub enum DelegateParam {
+    Field(usize),
+    Skip(Expr),
+    ExitArg(usize),
+}
+
+#[derive(Debug)]
+pub enum FieldType {
+    Regular,
+    Option,
+    Vec,
+    Set,
+    Map,
+    // Items are added through `buildstructor::Collection` or `buildstructor::MapCollection`.
+    Collection,
+    MapCollection,
+}
+
+impl FieldType {
+    pub fn is_collection(&self) -> bool {
+        matches!(
+            self,
+            FieldType::Vec
+                | FieldType::Set
+                | FieldType::Map
+                | FieldType::Collection
+                | FieldType::MapCollection
+        )
+    }
+}
+
+pub fn lower(model: BuilderModel) -> Result<Ir> {
+    // Either visibility is set explicitly or we default to super.
+    let vis = builder_visibility(&model, &model.vis, &model.vis)?;
+    let builder_vis = builder_visibility(
+        &model,
+        &model.vis,
+        &Visibility::Restricted(VisRestricted {
+            pub_token: Default::default(),
+            paren_token: Default::default(),
+            in_token: None,
+            path: Box::new(format_ident!("super").to_path()),
+        }),
+    )?;
+    let receiver = receiver(&model);
+    let mut builder_fields = builder_fields(&model)?;
+    let groups = groups(&model, &mut builder_fields)?;
+    let dynamic = dynamic(&model, &receiver, &groups)?;
+    let into_future = into_future(&model)?;
+    let blocking_exit = blocking_exit(&model)?;
+    let builder_return_type = builder_return_type(&model.delegate_return_type, &model.self_ty);
+    let exits = exits(&model, &builder_return_type);
+    Ok(Ir {
+        vis,
+        builder_vis,
+        module_name: format_ident!(
+            "__{}_{}_builder",
+            model.impl_name.to_string().to_lowercase(),
+            model.delegate_name.unraw().to_string().to_lowercase()
+        ),
+        impl_name: model.impl_name.clone(),
+        impl_generics: model.impl_generics.clone(),
+        self_ty: model.self_ty.clone(),
+        delegate_name: model.delegate_name.clone(),
+        delegate_generics: model.delegate_generics.clone(),
+        builder_name: format_ident!("__{}Builder", model.impl_name),
+        builder_return_type,
+        builder_entry: builder_entry(&model, &receiver)?,
+        builder_exit: builder_exit(&model, &receiver),
+        builder_fields,
+        groups,
+        dynamic,
+        into_future,
+        blocking_exit,
+        exits,
+        delegate_params: delegate_params(&model),
+        exit_args: exit_args(&model)?,
+        builder_generics: Ir::builder_generics(),
+        is_async: model.is_async,
+        doc: extract_docs(&model.attributes),
+        implicit_lifetime: implicit_lifetime(&model),
+        to_builder: to_builder(&model, &receiver)?,
+        receiver,
+        krate: model.krate.clone(),
+    })
+}
+
+// If the first parameter of a function is a reference it will have an implicit lifetime.
+fn implicit_lifetime(model: &BuilderModel) -> bool {
+    match model.delegate_args.first().zip(model.field_configs.first()) {
+        None => {}
+        // Exit args are not stored in the builder.
+        Some((_, config)) if config.is_exit_arg() => {}
+        Some((arg, _)) => match arg {
+            FnArg::Receiver(Receiver {
+                // If the lifetime has been set explicitly we can ignore it.
+                reference: Some((_, None)),
+                ..
+            }) => return true,
+            FnArg::Typed(arg) => {
+                if let Type::Reference(TypeReference { lifetime: None, .. }) = *arg.ty {
+                    return true;
+                }
+            }
+            _ => {}
+        },
+    }
+    false
+}
+
+fn extract_docs(attributes: &[Attribute]) -> Vec<Attribute> {
+    let doc_ident = format_ident!("doc");
+    attributes
+        .iter()
+        .filter(|a| a.path().get_ident() == Some(&doc_ident))
+        .cloned()
+        .collect()
+}
+
+fn receiver(model: &BuilderModel) -> Option<Receiver> {
+    model
+        .delegate_args
+        .iter()
+        .filter_map(|a| match a {
+            FnArg::Receiver(r) => Some(r.clone()),
+            FnArg::Typed(_) => None,
+        })
+        .next()
+}
+
+fn builder_visibility(
+    model: &BuilderModel,
+    visibility: &Visibility,
+    default: &Visibility,
+) -> Result<Visibility> {
+    // Either visibility is set explicitly or we default to super.
+    Ok(if let Some(visibility) = &model.config.visibility {
+        if visibility.trim().is_empty() {
+            default.clone()
+        } else {
+            syn::parse_str(visibility)?
+        }
+    } else if let Visibility::Inherited = model.vis {
+        default.clone()
+    } else {
+        visibility.clone()
+    })
+}
+
+fn builder_return_type(return_type: &ReturnType, target: &Type) -> ReturnType {
+    let mut return_type = return_type.clone();
+    if let ReturnType::Type(_, ty) = &mut return_type {
+        replace_self(ty, target);
+    }
+    return_type
+}
+
+fn replace_self(ty: &mut Type, target: &Type) {
+    let self_type = format_ident!("Self").to_type_path();
+    if let (Type::Path(path), Type::Path(self_ty)) = (ty, target) {
+        if path == &self_type {
+            *path = self_ty.clone();
+        } else {
+            for segment in path.path.segments.iter_mut() {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    for mut arg in args.args.iter_mut() {
+                        if let GenericArgument::Type(ty) = &mut arg {
+                            replace_self(ty, target);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn builder_fields(model: &BuilderModel) -> Result<Vec<BuilderField>> {
+    model
+        .delegate_args
+        .iter()
+        .zip(model.field_configs.iter())
+        .filter(|(_, config)| config.is_builder_field())
+        .filter_map(|(f, config)| match f {
+            FnArg::Typed(t) => {
+                // Every builder field needs a name, and the other parameter lists rely on fields lining up with parameters.
+                let ident = match try_match!(&*t.pat, Pat::Ident(x)=>x) {
+                    Ok(ident) => ident,
+                    Err(pat) => {
+                        return Some(Err(syn::Error::new(
+                            pat.span(),
+                            "builder parameters must be plain identifiers, use #[builder(skip = <expr>)] for other patterns",
+                        )))
+                    }
+                };
+                let optional_collection = match optional_collection(&t.ty, config) {
+                    Ok(optional_collection) => optional_collection,
+                    Err(e) => return Some(Err(e)),
+                };
+                let collection_ty = optional_collection.as_ref().unwrap_or(&t.ty);
+                let field_type = match field_type(collection_ty, config) {
+                    Ok(field_type) => field_type,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                let generic_types = generic_types(model, config, &field_type, collection_ty);
+
+                let into = into_override(config.into.as_ref(), || {
+                    t.ty.is_into_capable(&model.impl_generics, &model.delegate_generics)
+                });
+                let insert = insert_method(config, &field_type, collection_ty);
+                Some(validate_field_config(config, &field_type).map(|_| {
+                    BuilderField {
+                        ty: *t.ty.clone(),
+                        ty_into: into,
+                        name: builder_field_name(config, &ident.ident),
+                        singular: config.singular.clone(),
+                        generic_types,
+                        default: config.default.clone(),
+                        member: member(&ident.ident),
+                        overridable: config
+                            .overridable
+                            .as_ref()
+                            .map(|o| o.value)
+                            .unwrap_or(model.overridable && !field_type.is_collection()),
+                        group: None,
+                        insert,
+                        optional_collection,
+                        field_type,
+                    }
+                }))
+            }
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+// The struct member that a parameter is read back from by `to_builder`, positional parameters are named `_0`, `_1` etc.
+fn member(ident: &Ident) -> Member {
+    match ident
+        .to_string()
+        .strip_prefix('_')
+        .and_then(|idx| idx.parse::<u32>().ok())
+    {
+        Some(idx) => Member::Unnamed(Index {
+            index: idx,
+            span: ident.span(),
+        }),
+        None => Member::Named(ident.unraw()),
+    }
+}
+
+fn to_builder(model: &BuilderModel, receiver: &Option<Receiver>) -> Result<Option<Ident>> {
+    let to_builder = match &model.config.to_builder {
+        Some(to_builder) => to_builder,
+        None => return Ok(None),
+    };
+    if receiver.is_some() {
+        return Err(syn::Error::new(
+            to_builder.span(),
+            "#[builder(to_builder = \"<name>\")] can only be used on constructors",
+        ));
+    }
+    if !model.delegate_generics.params.is_empty() {
+        return Err(syn::Error::new(
+            to_builder.span(),
+            "#[builder(to_builder = \"<name>\")] cannot be used on constructors with generic parameters",
+        ));
+    }
+    Ok(Some(to_builder.clone()))
+}
+
+fn dynamic(model: &BuilderModel, receiver: &Option<Receiver>, groups: &[Group]) -> Result<bool> {
+    if !model.config.dynamic {
+        return Ok(false);
+    }
+    let span = model
+        .config
+        .span
+        .unwrap_or_else(|| model.delegate_name.span());
+    if receiver.is_some() {
+        return Err(syn::Error::new(
+            span,
+            "#[builder(dynamic)] can only be used on constructors",
+        ));
+    }
+    if !groups.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            "#[builder(dynamic)] cannot be used together with groups",
+        ));
+    }
+    Ok(true)
+}
+
+// Async builders only implement `IntoFuture` with `#[builder(into_future = true)]`, as the boxed future must be `Send` which not every constructor's future is.
+fn into_future(model: &BuilderModel) -> Result<bool> {
+    match &model.config.into_future {
+        Some(into_future) if !model.is_async => Err(syn::Error::new(
+            into_future.span(),
+            "#[builder(into_future = <bool>)] can only be used on async builders",
+        )),
+        Some(into_future) => Ok(into_future.value),
+        None => Ok(false),
+    }
+}
+
+fn blocking_exit(model: &BuilderModel) -> Result<Option<Ident>> {
+    match &model.config.blocking_exit {
+        Some(blocking_exit) if !model.is_async => Err(syn::Error::new(
+            blocking_exit.span(),
+            "#[builder(blocking_exit = \"<name>\")] can only be used on async builders",
+        )),
+        blocking_exit => Ok(blocking_exit.clone()),
+    }
+}
+
+fn exits(model: &BuilderModel, return_type: &ReturnType) -> Vec<Exit> {
+    let value: Type = match return_type {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => match result_types(ty) {
+            Some((ok, _)) => ok.clone(),
+            None => *ty.clone(),
+        },
+    };
+    let config = &model.config;
+    let arc = config.exit_arc.iter().map(|name| Exit {
+        name: name.clone(),
+        map: parse_quote!(std::sync::Arc::new),
+        output: parse_quote!(std::sync::Arc<#value>),
+    });
+    let boxed = config.exit_boxed.iter().map(|name| Exit {
+        name: name.clone(),
+        map: parse_quote!(std::boxed::Box::new),
+        output: parse_quote!(std::boxed::Box<#value>),
+    });
+    let custom = config.exits.iter().map(|exit| {
+        let mut output = exit.output.clone();
+        replace_self(&mut output, &model.self_ty);
+        Exit {
+            name: exit.name.clone(),
+            map: exit.map.clone(),
+            output,
+        }
+    });
+    arc.chain(boxed).chain(custom).collect()
+}
+
+// The `Ok` and `Err` types if `ty` is a `Result`.
+pub fn result_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Result" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 2 => {
+            match (&args.args[0], &args.args[1]) {
+                (GenericArgument::Type(ok), GenericArgument::Type(error)) => Some((ok, error)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Groups are declared on their members, flags given on any member apply to the whole group.
+fn groups(model: &BuilderModel, fields: &mut [BuilderField]) -> Result<Vec<Group>> {
+    // `builder_fields` rejects parameters that are not plain identifiers, so there is one config per field.
+    let configs = model
+        .delegate_args
+        .iter()
+        .zip(model.field_configs.iter())
+        .filter(|(arg, config)| matches!(arg, FnArg::Typed(_)) && config.is_builder_field())
+        .map(|(_, config)| config);
+    let mut groups: Vec<Group> = Vec::new();
+    for (field, config) in fields.iter_mut().zip(configs) {
+        let name = match &config.group {
+            Some(name) => name,
+            None => {
+                if let Some(flag) = config
+                    .exclusive
+                    .as_ref()
+                    .or(config.required.as_ref())
+                    .or(config.at_least_one.as_ref())
+                {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "#[builder(exclusive, required, at_least_one)] can only be used together with #[builder(group = \"<name>\")]",
+                    ));
+                }
+                continue;
+            }
+        };
+        if field.is_required() || field.is_collection() {
+            return Err(syn::Error::new(
+                name.span(),
+                "group members must be optional, use Option<T> or #[builder(default)]",
+            ));
+        }
+        let idx = match groups.iter().position(|g| g.name == name.value()) {
+            Some(idx) => idx,
+            None => {
+                groups.push(Group {
+                    name: name.value(),
+                    span: name.span(),
+                    members: Vec::new(),
+                    exclusive: false,
+                    required: false,
+                    at_least_one: false,
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[idx];
+        group.members.push(field.name.clone());
+        group.exclusive |= config.exclusive.as_ref().is_some_and(|f| f.value);
+        group.required |= config.required.as_ref().is_some_and(|f| f.value);
+        group.at_least_one |= config.at_least_one.as_ref().is_some_and(|f| f.value);
+        field.group = Some(idx);
+    }
+    for group in &groups {
+        if !group.exclusive && !group.at_least_one {
+            return Err(syn::Error::new(
+                group.span,
+                format!(
+                    "group '{}' must be declared as #[builder(exclusive)] or #[builder(at_least_one)] on at least one member",
+                    group.name
+                ),
+            ));
+        }
+        if group.required && !group.exclusive {
+            return Err(syn::Error::new(
+                group.span,
+                format!(
+                    "group '{}' is not exclusive, use #[builder(at_least_one)] rather than #[builder(required)]",
+                    group.name
+                ),
+            ));
+        }
+    }
+    Ok(groups)
+}
+
+fn validate_field_config(config: &FieldConfig, field_type: &FieldType) -> Result<()> {
+    if !field_type.is_collection() {
+        if let Some(name) = config.singular.as_ref().or(config.plural.as_ref()) {
+            return Err(syn::Error::new(
+                name.span(),
+                "#[builder(singular = \"<name>\", plural = \"<name>\")] can only be used on collections, use #[builder(setter = \"<name>\")] instead",
+            ));
+        }
+    }
+    if !field_type.is_collection() {
+        if let Some(insert) = &config.insert {
+            return Err(syn::Error::new(
+                insert.span(),
+                "#[builder(insert = \"<method>\")] can only be used on collections, use #[builder(collection = \"vec\"|\"set\"|\"map\")] if the type is not recognized as one",
+            ));
+        }
+    }
+    if field_type.is_collection() {
+        if let Some(overridable) = &config.overridable {
+            return Err(syn::Error::new(
+                overridable.span(),
+                "#[builder(overridable)] cannot be used on collections, they can already be added to many times",
+            ));
+        }
+    }
+    if !matches!(field_type, FieldType::Vec | FieldType::Set) {
+        if let Some(into) = &config.element_into {
+            return Err(syn::Error::new(
+                into.span(),
+                "#[builder(element_into)] can only be used on collections, use #[builder(into)] instead",
+            ));
+        }
+    }
+    if matches!(field_type, FieldType::Collection | FieldType::MapCollection) {
+        if let Some(into) = config.into.as_ref() {
+            return Err(syn::Error::new(
+                into.span(),
+                "#[builder(into)] cannot be used with #[builder(collection)] or #[builder(map_collection)], the setters already accept anything that the collection trait is implemented for",
+            ));
+        }
+    }
+    if !matches!(field_type, FieldType::Map) {
+        if let Some(into) = config.key_into.as_ref().or(config.value_into.as_ref()) {
+            return Err(syn::Error::new(
+                into.span(),
+                "#[builder(key_into, value_into)] can only be used on maps",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn builder_field_name(config: &FieldConfig, ident: &Ident) -> Ident {
+    config
+        .plural
+        .as_ref()
+        .or(config.setter.as_ref())
+        .cloned()
+        .unwrap_or_else(|| {
+            ident
+                .to_string()
+                .strip_prefix('_')
+                .map(|stripped| format_ident!("{}", stripped))
+                .unwrap_or_else(|| ident.clone())
+        })
+}
+
+// An explicit #[builder(into)] always wins over the heuristic.
+fn into_override(config: Option<&LitBool>, heuristic: impl FnOnce() -> bool) -> bool {
+    config.map(|into| into.value).unwrap_or_else(heuristic)
+}
+
+fn delegate_params(model: &BuilderModel) -> Vec<DelegateParam> {
+    let mut field_idx = 0;
+    let mut exit_arg_idx = 0;
+    model
+        .delegate_args
+        .iter()
+        .zip(model.field_configs.iter())
+        .filter_map(|(f, config)| match f {
+            FnArg::Typed(_) => Some(match &config.skip {
+                Some(skip) => DelegateParam::Skip(skip.clone()),
+                None if config.is_exit_arg() => {
+                    exit_arg_idx += 1;
+                    DelegateParam::ExitArg(exit_arg_idx - 1)
+                }
+                None => {
+                    field_idx += 1;
+                    DelegateParam::Field(field_idx - 1)
+                }
+            }),
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+// Exit args keep the name of the parameter so that they read the same on the exit as on the delegate.
+fn exit_args(model: &BuilderModel) -> Result<Vec<ExitArg>> {
+    model
+        .delegate_args
+        .iter()
+        .zip(model.field_configs.iter())
+        .filter_map(|(f, config)| match f {
+            FnArg::Typed(t) if config.is_exit_arg() => Some(match &*t.pat {
+                Pat::Ident(ident) => Ok(ExitArg {
+                    name: ident.ident.clone(),
+                    ty: *t.ty.clone(),
+                }),
+                pat => Err(syn::Error::new(
+                    pat.span(),
+                    "#[builder(exit_arg)] can only be used on parameters that are plain identifiers",
+                )),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct GenericTypes {
+    pub key_type: Option<Type>,
+    pub key_into: bool,
+    pub value_type: Option<Type>,
+    pub value_into: bool,
+    pub generic_type: Option<Type>,
+    pub generic_into: bool,
+}
+
+// `Option<Vec<T>>` and friends are collections rather than options, so that elements can be added one at a time.
+fn optional_collection(ty: &Type, config: &FieldConfig) -> Result<Option<Type>> {
+    if ty.last_ident() != Some(format_ident!("Option")) {
+        return Ok(None);
+    }
+    match ty.generic_args().and_then(|args| args.first()) {
+        Some(GenericArgument::Type(inner)) if field_type(inner, config)?.is_collection() => {
+            Ok(Some(inner.clone()))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn generic_types(
+    model: &BuilderModel,
+    config: &FieldConfig,
+    field_type: &FieldType,
+    ty: &Type,
+) -> GenericTypes {
+    let args = ty.generic_args();
+    match (
+        &field_type,
+        args.and_then(|args| args.iter().next()),
+        args.and_then(|args| args.iter().nth(1)),
+    ) {
+        (FieldType::Option, Some(GenericArgument::Type(option_type)), None) => GenericTypes {
+            generic_type: Some(option_type.clone()),
+            generic_into: into_override(config.into.as_ref(), || {
+                option_type.is_into_capable(&model.impl_generics, &model.delegate_generics)
+            }),
+            ..Default::default()
+        },
+        (FieldType::Vec | FieldType::Set, Some(GenericArgument::Type(collection_type)), None) => {
+            GenericTypes {
+                generic_type: Some(collection_type.clone()),
+                generic_into: into_override(
+                    config.element_into.as_ref().or(config.into.as_ref()),
+                    || {
+                        collection_type
+                            .is_into_capable(&model.impl_generics, &model.delegate_generics)
+                    },
+                ),
+                ..Default::default()
+            }
+        }
+        (
+            FieldType::Map,
+            Some(GenericArgument::Type(key_type)),
+            Some(GenericArgument::Type(value_type)),
+        ) => GenericTypes {
+            key_type: Some(key_type.clone()),
+            key_into: into_override(config.key_into.as_ref().or(config.into.as_ref()), || {
+                key_type.is_into_capable(&model.impl_generics, &model.delegate_generics)
+            }),
+            value_type: Some(value_type.clone()),
+            value_into: into_override(config.value_into.as_ref().or(config.into.as_ref()), || {
+                value_type.is_into_capable(&model.impl_generics, &model.delegate_generics)
+            }),
+            ..Default::default()
+        },
+        _ => GenericTypes::default(),
+    }
+}
+
+fn builder_entry(model: &BuilderModel, receiver: &Option<Receiver>) -> Result<Ident> {
+    let method_name = model.delegate_name.to_string();
+    match (&model.config.entry, receiver) {
+        (Some(name), _) => return Ok(format_ident!("{}", name)),
+        // constructor
+        (None, None) => match (method_name.as_str(), method_name.strip_suffix("_new")) {
+            ("new", _) => return Ok(format_ident!("builder")),
+            (_, Some(stripped)) => return Ok(format_ident!("{}_builder", stripped)),
+            _ => {}
+        },
+        _ => {}
+    }
+    Err(syn::Error::new(
+        model
+            .config
+            .span
+            .unwrap_or_else(|| model.delegate_name.span()),
+        format!(
+            "#[builder(entry = \"<name>\")] cannot be defaulted for 'fn {}' and must be specified via annotation", method_name
+        ),
+    ))
+}
+
+fn builder_exit(model: &BuilderModel, receiver: &Option<Receiver>) -> Ident {
+    match (&model.config.exit, receiver) {
+        (Some(name), _) => format_ident!("{}", name),
+        // constructor
+        (None, None) => format_ident!("build"),
+        // call
+        (None, Some(_)) => format_ident!("call"),
+    }
+}
+
+// Types are classified by the last segment of their path, so `std::collections::HashMap<K, V>` is a map just like `HashMap<K, V>`.
+// #[builder(collection = "...")] overrides the classification for types that the name gets wrong.
+fn field_type(ty: &Type, config: &FieldConfig) -> Result<FieldType> {
+    // The traits decide what may be inserted, so no type arguments are needed.
+    if config.trait_collection.is_some() {
+        return Ok(FieldType::Collection);
+    }
+    if config.trait_map_collection.is_some() {
+        return Ok(FieldType::MapCollection);
+    }
+    let field_type = match ty.last_ident() {
+        Some(f) if f == format_ident!("Option") => FieldType::Option,
+        Some(f) if f == format_ident!("Vec") => FieldType::Vec,
+        Some(f) if f.to_string().ends_with("Stack") => FieldType::Vec,
+        Some(f) if f.to_string().ends_with("Heap") => FieldType::Vec,
+        Some(f) if f.to_string().ends_with("Deque") => FieldType::Vec,
+        Some(f) if f.to_string().ends_with("Buffer") => FieldType::Vec,
+        Some(f) if f == format_ident!("LinkedList") => FieldType::Vec,
+        Some(f) if f.to_string().ends_with("Set") => FieldType::Set,
+        Some(f) if f.to_string().ends_with("Map") => FieldType::Map,
+        _ => FieldType::Regular,
+    };
+    let field_type = match config.collection.as_ref().map(|c| (c, c.value())) {
+        None => field_type,
+        Some((_, c)) if c == "vec" => FieldType::Vec,
+        Some((_, c)) if c == "set" => FieldType::Set,
+        Some((_, c)) if c == "map" => FieldType::Map,
+        Some((_, c)) if c == "none" && field_type.is_collection() => FieldType::Regular,
+        Some((_, c)) if c == "none" => field_type,
+        Some((collection, _)) => return Err(syn::Error::new(
+            collection.span(),
+            "#[builder(collection = \"...\")] must be one of \"vec\", \"set\", \"map\" or \"none\"",
+        )),
+    };
+    // Without type arguments there is nothing to insert, e.g. an associated type such as `T::Map`.
+    let type_args = ty
+        .generic_args()
+        .map(|args| {
+            args.iter()
+                .filter(|arg| matches!(arg, GenericArgument::Type(_)))
+                .count()
+        })
+        .unwrap_or_default();
+    Ok(match field_type {
+        FieldType::Map if type_args < 2 => FieldType::Regular,
+        FieldType::Option | FieldType::Vec | FieldType::Set if type_args < 1 => FieldType::Regular,
+        field_type => field_type,
+    })
+}
+
+// Deques and `LinkedList` have no `push`, elements are added to the back instead.
+fn insert_method(config: &FieldConfig, field_type: &FieldType, ty: &Type) -> Option<Ident> {
+    if !matches!(field_type, FieldType::Vec | FieldType::Set | FieldType::Map) {
+        return None;
+    }
+    if let Some(insert) = &config.insert {
+        return Some(insert.clone());
+    }
+    let name = ty.last_ident().map(|i| i.to_string()).unwrap_or_default();
+    Some(match field_type {
+        FieldType::Vec if name.ends_with("Deque") || name == "LinkedList" => {
+            format_ident!("push_back")
+        }
+        FieldType::Vec => format_ident!("push"),
+        _ => format_ident!("insert"),
+    })
+}
+
+// States are named by their path in the runtime crate so that the builder alias can be written by users.
+fn state_type(krate: &Path, state: &str, ty: &Type) -> Type {
+    let state = format_ident!("{}", state);
+    parse_quote!(#krate::state::#state<#ty>)
+}
+
+impl Ir {
+    pub fn delegate_args(&self) -> Vec<TokenStream> {
+        self.delegate_params
+            .iter()
+            .map(|param| match param {
+                DelegateParam::Field(idx) => {
+                    let field = &self.builder_fields[*idx];
+                    let idx = Index::from(*idx);
+                    match &field.default {
+                        Some(default) => quote! {
+                            self.fields.#idx.into().into_lazy().unwrap_or_else(|| #default)
+                        },
+                        None => quote! {
+                            self.fields.#idx.into().into_value()
+                        },
+                    }
+                }
+                DelegateParam::Skip(skip) => quote! {
+                    #skip
+                },
+                DelegateParam::ExitArg(idx) => {
+                    let name = &self.exit_args[*idx].name;
+                    quote! {
+                        #name
+                    }
+                }
+            })
+            .collect()
+    }
+
+    // The parameters that the exit takes in addition to `self`.
+    pub fn exit_params(&self) -> Vec<TokenStream> {
+        self.exit_args
+            .iter()
+            .map(|ExitArg { name, ty }| quote! { #name: #ty })
+            .collect()
+    }
+
+    // Forwards the exit args from one exit to another.
+    pub fn exit_arg_names(&self) -> Vec<&Ident> {
+        self.exit_args.iter().map(|a| &a.name).collect()
+    }
+
+    pub fn tuple_field(&self, idx: usize) -> Expr {
+        Expr::Field(ExprField {
+            attrs: vec![],
+            base: Box::new(Self::fields()),
+            dot_token: Default::default(),
+            member: Member::Unnamed(Index::from(idx)),
+        })
+    }
+
+    pub fn fields() -> Expr {
+        Expr::Field(ExprField {
+            attrs: vec![],
+            base: Box::new(Expr::Path(format_ident!("self").to_expr_path())),
+            dot_token: Default::default(),
+            member: Member::Named(format_ident!("fields")),
+        })
+    }
+
+    pub fn builder_state_type_initial(&self) -> Type {
+        Type::Tuple(TypeTuple {
+            paren_token: Default::default(),
+            elems: Punctuated::from_iter(
+                self.builder_fields
+                    .iter()
+                    .map(|field| {
+                        if field.is_required() {
+                            state_type(&self.krate, "Required", &field.ty)
+                        } else {
+                            state_type(&self.krate, "Optional", &field.ty)
+                        }
+                    })
+                    .chain(self.group_states("GroupUnset")),
+            )
+            .with_trailing(),
+        })
+    }
+
+    // The state of a builder created from an existing value, collections are pre-filled so that they can still be extended.
+    pub fn builder_state_type_existing(&self) -> Type {
+        Type::Tuple(TypeTuple {
+            paren_token: Default::default(),
+            elems: Punctuated::from_iter(
+                self.builder_fields
+                    .iter()
+                    .map(|field| {
+                        if field.is_collection() {
+                            state_type(&self.krate, "Optional", &field.ty)
+                        } else {
+                            state_type(&self.krate, "Existing", &field.ty)
+                        }
+                    })
+                    .chain(self.group_states("GroupExisting")),
+            )
+            .with_trailing(),
+        })
+    }
+
+    pub fn builder_state_existing(&self) -> Vec<TokenStream> {
+        self.builder_fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let value = format_ident!("__f{}", idx);
+                if field.is_collection() {
+                    quote! {__Optional::new(Some(#value))}
+                } else {
+                    quote! {__Existing::new(#value)}
+                }
+            })
+            .chain(self.groups.iter().map(|_| quote! {__GroupExisting}))
+            .collect()
+    }
+
+    pub fn builder_state_initial(&self) -> Vec<TokenStream> {
+        self.builder_fields
+            .iter()
+            .map(|field| {
+                if field.is_required() {
+                    quote! {__Required::new()}
+                } else {
+                    quote! {__Optional::new(None)}
+                }
+            })
+            .chain(self.groups.iter().map(|_| quote! {__GroupUnset}))
+            .collect()
+    }
+
+    fn group_states(&self, state: &str) -> Vec<Type> {
+        let krate = &self.krate;
+        let state = format_ident!("{}", state);
+        self.groups
+            .iter()
+            .map(|_| parse_quote!(#krate::state::#state))
+            .collect()
+    }
+
+    // Groups are stored after the fields in the builder state.
+    pub fn group_slot(&self, group: usize) -> usize {
+        self.builder_fields.len() + group
+    }
+
+    fn slots(&self) -> usize {
+        self.builder_fields.len() + self.groups.len()
+    }
+
+    pub fn param_generics(&self) -> Generics {
+        Generics {
+            params: Punctuated::from_iter(
+                (0..self.slots()).map(|idx| format_ident!("__P{}", idx).to_generic_param(None)),
+            ),
+            ..Default::default()
+        }
+    }
+
+    pub fn param_predicates(&self) -> Vec<WherePredicate> {
+        self.builder_fields
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| {
+                let param = format_ident!("__P{}", idx);
+                let provided = f.provided_trait();
+                let ty = &f.ty;
+                parse_quote!(#param: #provided<#ty>)
+            })
+            .chain(
+                self.groups
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, g)| g.needs_member())
+                    .map(|(idx, g)| {
+                        let param = format_ident!("__P{}", self.group_slot(idx));
+                        let provided = g.provided_trait();
+                        parse_quote!(#param: #provided)
+                    }),
+            )
+            .collect()
+    }
+
+    fn builder_generics() -> Generics {
+        Generics {
+            params: Punctuated::from_iter(vec![format_ident!("__P").to_generic_param(None)]),
+            ..Default::default()
+        }
+    }
+
+    pub fn builder_type_generics(&self) -> Generics {
+        Generics {
+            params: Punctuated::from_iter(
+                (0..self.slots())
+                    .map(|idx| GenericParam::Type(TypeParam::from(format_ident!("__{}", idx)))),
+            ),
+            ..Default::default()
+        }
+    }
+}
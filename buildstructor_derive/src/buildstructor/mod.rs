@@ -0,0 +1,682 @@
+pub mod analyze;
+pub mod codegen;
+pub mod lower;
+pub mod parse;
+pub mod utils;
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::Ast;
+    use syn::parse_quote;
+
+    pub fn single_field_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(simple: usize) -> Foo {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn pub_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                pub fn new(simple: usize) -> Foo {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn multi_field_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(simple: usize, simple2: usize) -> Foo {
+                    Self { simple, simple2 }
+                }
+            }
+        )
+    }
+
+    pub fn fallible_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(simple: usize) -> Result<Foo, String> {
+                    Ok(Self { simple })
+                }
+            }
+        )
+    }
+
+    pub fn async_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                async fn new(simple: usize) -> Foo {
+                    Foo { simple }
+                }
+            }
+        )
+    }
+
+    pub fn into_future_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder(into_future = true)]
+                async fn new(simple: usize) -> Foo {
+                    Foo { simple }
+                }
+            }
+        )
+    }
+
+    pub fn async_receiver_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Client {
+                #[builder(entry = "request", exit = "send")]
+                async fn request_send(&self, path: String) -> String {
+                    path
+                }
+            }
+        )
+    }
+
+    pub fn blocking_exit_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder(blocking_exit = "build_blocking")]
+                async fn new(simple: usize) -> Foo {
+                    Foo { simple }
+                }
+            }
+        )
+    }
+
+    pub fn exits_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder(
+                    exit_arc = "build_arc",
+                    exit_boxed = "build_boxed",
+                    exit(name = "build_named", map = Named::new, output = "Named<Self>")
+                )]
+                fn new(simple: usize) -> Result<Self, Error> {
+                    Ok(Foo { simple })
+                }
+            }
+        )
+    }
+
+    pub fn generic_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl<T> Foo<T> {
+                #[builder]
+                fn new(simple: T) -> Foo<T> {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn into_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new<T: Into<String>>(simple: T) -> Foo {
+                    Foo {
+                        simple: simple.into(),
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn into_where_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new<T>(simple: T) -> Foo
+                where
+                    T: Into<String>,
+                {
+                    Foo {
+                        simple: simple.into(),
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn option_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(option: Option<usize>) -> Foo {
+                    Foo { option }
+                }
+            }
+        )
+    }
+
+    pub fn collections_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    simple: usize,
+                    set: HashSet<String>,
+                    map: HashMap<String, String>,
+                    vec: Vec<String>,
+                    btmap: BTreeMap<String, String>,
+                    btset: BTreeSet<String>,
+                ) -> Foo {
+                    Self {
+                        simple,
+                        set,
+                        map,
+                        vec,
+                        btmap,
+                        btset,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn collections_generics_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new<K: Into<String> + Eq + Hash, V: Into<String>>(param: HashMap<K, V>) -> Foo {
+                    Self {
+                        param: param
+                            .into_iter()
+                            .map(|(k, v)| (k.into(), v.into()))
+                            .collect(),
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn collections_option_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(param: HashMap<Option<String>, Option<String>>) -> Foo {
+                    Self {
+                        param: param
+                            .into_iter()
+                            .map(|(k, v)| (k.into(), v.into()))
+                            .collect(),
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn returns_self_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(simple: usize) -> Self {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn multiple_generics_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl<T> Request<T> {
+                #[builder]
+                pub fn fake_new<K, V>(
+                    headers: Vec<(K, V)>,
+                    uri: Option<http::Uri>,
+                    method: Option<http::Method>,
+                    body: T,
+                ) -> http::Result<Request<T>>
+                where
+                    HeaderName: TryFrom<K>,
+                    <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+                    HeaderValue: TryFrom<V>,
+                    <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+                {
+                    let mut builder = http::request::Builder::new();
+                    for (key, value) in headers {
+                        builder = builder.header(key, value);
+                    }
+                    let req = builder.body(body)?;
+
+                    Ok(Self { inner: req })
+                }
+            }
+        )
+    }
+
+    pub fn collections_generics_test_case2() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Collections {
+                #[builder]
+                fn new<K: Into<String> + Eq + Hash, V: Into<String>>(
+                    map: HashMap<K, V>,
+                    set: HashSet<K>,
+                ) -> Collections {
+                    Self {
+                        map: map.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+                        set: set.into_iter().map(|v| v.into()).collect(),
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn self_receiver_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Client {
+                #[builder(entry = "message", exit = "send")]
+                fn call_with_no_return(self, simple: String) {}
+
+                #[builder(entry = "message_ref", exit = "send")]
+                fn call_with_no_return_ref(&self, simple: String) {}
+
+                #[builder(entry = "query", exit = "call")]
+                fn call_with_return(self, simple: String) -> bool {
+                    true
+                }
+
+                #[builder(entry = "query_ref", exit = "call")]
+                fn call_with_return_ref(&self, simple: String) -> bool {
+                    true
+                }
+            }
+        )
+    }
+
+    pub fn doc_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                /// Test doc
+                #[builder]
+                fn new(simple: usize) -> Foo {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn reference_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                /// Test doc
+                #[builder]
+                fn new(simple: &usize) -> Foo {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn self_reference_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Client {
+                #[builder(entry = "builder")]
+                fn new(&self) {}
+            }
+        )
+    }
+
+    pub fn lifetime_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl<'a> Foo<'a> {
+                #[builder]
+                fn new(simple: &'a String) -> Foo<'a> {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn specialization_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo<usize> {
+                #[builder]
+                fn bound_new(simple: usize) -> Foo<usize> {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn specialization_returns_self_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo<usize> {
+                #[builder]
+                fn bound_new(simple: usize) -> Self {
+                    Self { simple }
+                }
+            }
+        )
+    }
+
+    pub fn associated_types_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl<T: MyTrait> Foo<T> {
+                #[builder]
+                pub fn new(foo: T, bar: T::Bar) -> Foo<T> {
+                    Foo { foo, bar }
+                }
+            }
+        )
+    }
+
+    pub fn default_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    simple: usize,
+                    #[builder(default)] defaulted: usize,
+                    #[builder(default = Duration::from_secs(30))] timeout: Duration,
+                ) -> Foo {
+                    Self {
+                        simple,
+                        defaulted,
+                        timeout,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn exit_arg_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Client {
+                #[builder(entry = "message", exit = "send")]
+                fn call_with_no_return(
+                    self,
+                    #[builder(exit_arg)] transaction: &mut Transaction,
+                    simple: String,
+                    #[builder(exit_arg)] deadline: Instant,
+                ) {
+                }
+            }
+        )
+    }
+
+    pub fn qualified_collections_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl<T: Settings> Foo<T> {
+                #[builder]
+                fn new(
+                    names: ::std::vec::Vec<String>,
+                    tags: std::collections::HashSet<String>,
+                    headers: std::collections::HashMap<String, String>,
+                    port: std::option::Option<u16>,
+                    settings: T::Map,
+                ) -> Foo<T> {
+                    Self {
+                        names,
+                        tags,
+                        headers,
+                        port,
+                        settings,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn collection_override_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    jobs: VecDeque<String>,
+                    steps: LinkedList<String>,
+                    #[builder(collection = "none")] config: ConfigMap<String, String>,
+                    #[builder(collection = "vec", insert = "add")] ids: IdBag<usize>,
+                    #[builder(insert = "push_front")] urgent_jobs: VecDeque<String>,
+                ) -> Foo {
+                    Self {
+                        jobs,
+                        steps,
+                        config,
+                        ids,
+                        urgent_jobs,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn trait_collection_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(collection)] hosts: Hosts,
+                    #[builder(map_collection)] headers: HeaderMap,
+                ) -> Foo {
+                    Self { hosts, headers }
+                }
+            }
+        )
+    }
+
+    pub fn optional_collection_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    names: Option<Vec<String>>,
+                    headers: Option<HashMap<String, String>>,
+                    #[builder(collection = "none")] ids: Option<Vec<usize>>,
+                ) -> Foo {
+                    Self {
+                        names,
+                        headers,
+                        ids,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn skip_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    simple: usize,
+                    #[builder(skip = SystemClock)] clock: SystemClock,
+                    #[builder(skip)] metrics: Metrics,
+                ) -> Foo {
+                    Self {
+                        simple,
+                        clock,
+                        metrics,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn setter_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(setter = "r#type")] ty: String,
+                    #[builder(setter = "config")] cfg: Option<String>,
+                    #[builder(singular = "datum")] data: Vec<String>,
+                    #[builder(singular = "header", plural = "headers")] header_map: HashMap<
+                        String,
+                        String,
+                    >,
+                ) -> Foo {
+                    Self {
+                        ty,
+                        cfg,
+                        data,
+                        header_map,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn into_override_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(into)] name: Cow<'static, str>,
+                    #[builder(into = false)] path: PathBuf,
+                    #[builder(into)] count: Option<u64>,
+                    #[builder(element_into = false)] tags: Vec<String>,
+                    #[builder(key_into = false, value_into)] labels: HashMap<String, Arc<str>>,
+                ) -> Foo {
+                    Self {
+                        name,
+                        path,
+                        count,
+                        tags,
+                        labels,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn to_builder_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder(to_builder = "to_builder")]
+                fn new(name: String, count: Option<usize>, tags: Vec<String>) -> Foo {
+                    Self { name, count, tags }
+                }
+            }
+        )
+    }
+
+    pub fn overridable_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(overridable)] name: String,
+                    #[builder(overridable)] count: Option<usize>,
+                    path: String,
+                ) -> Foo {
+                    Self { name, count, path }
+                }
+            }
+        )
+    }
+
+    pub fn group_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(group = "auth", exclusive, required)] password: Option<String>,
+                    #[builder(group = "auth")] token: Option<String>,
+                    name: String,
+                ) -> Foo {
+                    Self {
+                        password,
+                        token,
+                        name,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn at_least_one_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder]
+                fn new(
+                    #[builder(group = "address", at_least_one)] url: Option<String>,
+                    #[builder(group = "address")] host: Option<String>,
+                    #[builder(group = "address")] socket_path: Option<String>,
+                ) -> Foo {
+                    Self {
+                        url,
+                        host,
+                        socket_path,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn dynamic_test_case() -> Ast {
+        parse_quote!(
+            #[buildstructor]
+            impl Foo {
+                #[builder(dynamic)]
+                fn new(
+                    name: String,
+                    #[builder(default = 3)] count: usize,
+                    description: Option<String>,
+                    tags: Vec<String>,
+                ) -> Foo {
+                    Self {
+                        name,
+                        count,
+                        description,
+                        tags,
+                    }
+                }
+            }
+        )
+    }
+}
@@ -159,6 +159,7 @@ pub trait AngleBracketedGenericArgumentsExt {
     fn insert(self, idx: usize, ty: Type) -> Self;
     fn maybe(self) -> Option<AngleBracketedGenericArguments>;
     fn with_implicit_lifetime(self, implicit_lifetime: bool) -> Self;
+    fn with_elided_lifetime(self, elided_lifetime: bool) -> Self;
 }
 
 impl AngleBracketedGenericArgumentsExt for AngleBracketedGenericArguments {
@@ -184,6 +185,16 @@ impl AngleBracketedGenericArgumentsExt for AngleBracketedGenericArguments {
         }
         self
     }
+
+    fn with_elided_lifetime(mut self, elided_lifetime: bool) -> Self {
+        if elided_lifetime {
+            self.args.insert(
+                0,
+                GenericArgument::Lifetime(Lifetime::new("'_", self.span())),
+            )
+        }
+        self
+    }
 }
 
 pub trait ExprTupleExt {
@@ -210,9 +221,9 @@ impl TypeTupleExt for TypeTuple {
 
 pub trait TypeExt {
     fn raw_ident(&self) -> Option<Ident>;
+    fn last_ident(&self) -> Option<Ident>;
     fn generic_args(&self) -> Option<&Punctuated<GenericArgument, Token![,]>>;
     fn wrap_in_generic(&self, ident: Ident) -> Type;
-    fn wrap_in_generic_with_module(&self, module: &Ident, ident: Ident) -> Type;
     fn to_path(&self) -> Option<Path>;
     fn parse(name: &'static str) -> Type;
     fn is_into_capable(&self, impl_generics: &Generics, constructor_generics: &Generics) -> bool;
@@ -231,13 +242,18 @@ impl TypeExt for Type {
         }
     }
 
+    // The name of the type without its module path, e.g. `HashMap` for `std::collections::HashMap<K, V>`.
+    fn last_ident(&self) -> Option<Ident> {
+        last_segment(self).map(|segment| segment.ident.clone())
+    }
+
     fn generic_args(&self) -> Option<&Punctuated<GenericArgument, Token![,]>> {
-        if let Type::Path(path) = self {
-            if path.path.leading_colon.is_none() && path.path.segments.len() == 1 {
-                if let PathArguments::AngleBracketed(args) = &path.path.segments[0].arguments {
-                    return Some(&args.args);
-                }
-            }
+        if let Some(PathSegment {
+            arguments: PathArguments::AngleBracketed(args),
+            ..
+        }) = last_segment(self)
+        {
+            return Some(&args.args);
         }
         None
     }
@@ -260,30 +276,6 @@ impl TypeExt for Type {
         })
     }
 
-    fn wrap_in_generic_with_module(&self, module: &Ident, ident: Ident) -> Type {
-        Type::Path(TypePath {
-            qself: None,
-            path: Path {
-                leading_colon: None,
-                segments: Punctuated::from_iter(vec![
-                    PathSegment {
-                        ident: module.clone(),
-                        arguments: Default::default(),
-                    },
-                    PathSegment {
-                        ident,
-                        arguments: PathArguments::AngleBracketed(AngleBracketedGenericArguments {
-                            colon2_token: Default::default(),
-                            lt_token: Default::default(),
-                            args: Punctuated::from_iter(vec![GenericArgument::Type(self.clone())]),
-                            gt_token: Default::default(),
-                        }),
-                    },
-                ]),
-            },
-        })
-    }
-
     fn to_path(&self) -> Option<Path> {
         if let Type::Path(path) = self {
             return Some(path.path.clone());
@@ -343,6 +335,14 @@ impl TypeExt for Type {
     }
 }
 
+// Qualified self types such as `<T as Trait>::Map` are associated types, so they never have a last segment to classify.
+fn last_segment(ty: &Type) -> Option<&PathSegment> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last(),
+        _ => None,
+    }
+}
+
 pub trait PunctuatedExt<T, P> {
     fn with_trailing(self) -> Self;
 }
@@ -0,0 +1,419 @@
+//! Procedural macros for [buildstructor](https://docs.rs/buildstructor), depend on `buildstructor` rather than using this crate directly.
+#![allow(clippy::needless_doctest_main)]
+extern crate core;
+
+use inflector::Inflector;
+
+use proc_macro::TokenStream;
+use proc_macro2::Ident;
+use quote::{format_ident, ToTokens};
+use syn::__private::TokenStream2;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse2, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Field, Fields, FnArg,
+    ImplItem, Meta, Token,
+};
+mod buildstructor;
+use crate::buildstructor::analyze;
+use crate::buildstructor::analyze::{BuildstructorConfig, DeriveConfig, FieldConfig};
+use crate::buildstructor::codegen;
+use crate::buildstructor::lower;
+use crate::buildstructor::parse;
+use crate::buildstructor::utils::TypeExt;
+use crate::parse::Ast;
+
+/// Derive a builder from a constructor!
+///
+/// 1. Import the `buildstructor` macro.
+/// 2. Annotate your `impl` containing a `new` function.
+/// 3. Use your automatically derived builder.
+///
+/// TLDR: Write your Rust constructors as you would normally, and get a generated builder.
+///
+/// # Examples
+///
+/// ```rust
+/// use buildstructor::buildstructor;
+///
+/// struct MyStruct {
+///     sum: usize,
+/// }
+///
+/// #[buildstructor]
+/// impl MyStruct {
+///     #[builder]
+///     fn new(a: usize, b: usize) -> MyStruct {
+///         Self { sum: a + b }
+///     }
+/// }
+/// # #[allow(clippy::needless_doctest_main)]
+/// # fn main() {
+///   let mine = MyStruct::builder().a(2).b(3).build();
+///   assert_eq!(mine.sum, 5);
+/// # }
+/// ```
+#[proc_macro_attribute]
+pub fn buildstructor(args: TokenStream, item: TokenStream) -> TokenStream {
+    let config = parse_macro_input!(args as BuildstructorConfig);
+    do_buildstructor(false, config, item)
+}
+
+#[proc_macro_attribute]
+#[deprecated(
+    since = "0.2.0",
+    note = "#[buildstructor::builder] should be migrated to #[buildstructor::buildstructor] and individual methods annotated with #[builder]"
+)]
+pub fn builder(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    do_buildstructor(true, BuildstructorConfig::default(), item)
+}
+
+/// Derive a builder AND a constructor!
+///
+/// 1. Import the `Builder` macro.
+/// 2. Use your automatically derived builder.
+///
+/// TLDR: Write your Rust constructors as you would normally, and get a generated builder.
+///
+/// # Examples
+///
+/// ```rust
+/// use buildstructor::Builder;
+///
+/// #[derive(Builder)]
+/// struct MyStruct {
+///     sum: usize,
+/// }
+///
+/// # #[allow(clippy::needless_doctest_main)]
+/// # fn main() {
+///   let mine = MyStruct::builder().sum(3).build();
+///   assert_eq!(mine.sum, 3);
+/// # }
+/// ```
+///
+/// The struct may be annotated with `#[builder(...)]` using the same keys as a `#[builder]` method, plus `constructor = "<name>"` to rename the generated constructor.
+/// Fields may be annotated with `#[builder(...)]` using the same keys as constructor parameters.
+///
+/// Structs annotated with `#[builder(to_builder = "<name>")]` also get a method of that name which returns a builder initialised with the existing field values.
+///
+/// On enums a constructor and builder are generated for each variant with named fields, e.g. `Event::Created` gets `Event::created_builder()`.
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(item: TokenStream) -> TokenStream {
+    do_derive(item)
+}
+
+fn do_buildstructor(
+    legacy_default_builders: bool,
+    config: BuildstructorConfig,
+    item: TokenStream,
+) -> TokenStream {
+    match parse::parse(item.clone().into()).map_err(|e| e.into_compile_error()) {
+        Ok(mut ast) => {
+            // We have the AST, we can return the token stream regardless of if there was success or not as long as we sanitize it of helper attributes.
+            let mut results: Vec<proc_macro::TokenStream> =
+                match analyze::analyze(legacy_default_builders, &ast)
+                    .map_err(|e| e.into_compile_error())
+                {
+                    Ok(builders) => builders
+                        .into_iter()
+                        .map(|builder| match builder {
+                            Ok(mut builder) => {
+                                builder.overridable = config.overridable;
+                                if let Some(krate) = &config.krate {
+                                    builder.krate = krate.clone();
+                                }
+                                let ir =
+                                    lower::lower(builder).map_err(|e| e.into_compile_error())?;
+                                let code_gen =
+                                    codegen::codegen(ir).map_err(|e| e.into_compile_error())?;
+                                Ok(code_gen)
+                            }
+                            Err(e) => Err(e.into_compile_error()),
+                        })
+                        .map(|r: Result<TokenStream2, TokenStream2>| match r {
+                            Ok(r) => r.into(),
+                            Err(e) => e.into(),
+                        })
+                        .collect(),
+                    Err(e) => {
+                        vec![e.into()]
+                    }
+                };
+
+            // Relax clippy on constructors
+            allow_many_params(&mut ast);
+
+            // Now sanitize the AST of any helper attributes.
+            sanitize(&mut ast);
+
+            // Finally output the results.
+            let sanitized_token_stream = ast.item.to_token_stream();
+            results.insert(0, sanitized_token_stream.into());
+            TokenStream::from_iter(results)
+        }
+        Err(e) => {
+            // The parse failed so emit the original token stream as some editors rely on this.
+            TokenStream::from_iter([item, e.into()])
+        }
+    }
+}
+
+fn allow_many_params(ast: &mut Ast) {
+    let allow_params: Attribute = parse_quote!(#[allow(clippy::too_many_arguments)]);
+    ast.item.items.iter_mut().for_each(|item| {
+        if let ImplItem::Fn(m) = item {
+            if m.attrs
+                .iter()
+                .any(|attr| attr.path().get_ident() == Some(&format_ident!("builder")))
+            {
+                m.attrs.push(allow_params.clone())
+            }
+        }
+    });
+}
+
+fn sanitize(ast: &mut Ast) {
+    ast.item.items.iter_mut().for_each(|item| {
+        if let ImplItem::Fn(m) = item {
+            m.attrs
+                .retain(|a| a.path().get_ident() != Some(&format_ident!("builder")));
+            m.sig.inputs.iter_mut().for_each(|input| {
+                if let FnArg::Typed(t) = input {
+                    t.attrs
+                        .retain(|a| a.path().get_ident() != Some(&format_ident!("builder")));
+                }
+            });
+        }
+    });
+}
+
+pub(crate) fn do_derive(item: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse2(item.into()).unwrap();
+    match derive(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let self_ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = &input.generics.split_for_impl();
+    let config = derive_config(&input.attrs)?;
+    let buildstructor = match &config.krate {
+        Some(krate) => quote::quote!(#krate::buildstructor(crate = #krate)),
+        None => quote::quote!(buildstructor::buildstructor),
+    };
+    let constructors = match &input.data {
+        Data::Struct(s) => vec![derive_constructor(
+            input,
+            &s.fields,
+            config,
+            format_ident!("new"),
+            quote::quote!(Self),
+            self_ty.to_string(),
+        )?],
+        Data::Enum(e) => {
+            if let Some(constructor) = &config.constructor {
+                return Err(syn::Error::new(
+                    constructor.span(),
+                    "'constructor' must be specified on enum variants rather than the enum",
+                ));
+            }
+            if let Some(entry) = config.builder.iter().find(|m| m.path().is_ident("entry")) {
+                return Err(syn::Error::new(
+                    entry.span(),
+                    "'entry' must be specified on enum variants rather than the enum",
+                ));
+            }
+            if let Some(to_builder) = config
+                .builder
+                .iter()
+                .find(|m| m.path().is_ident("to_builder"))
+            {
+                return Err(syn::Error::new(
+                    to_builder.span(),
+                    "'to_builder' is only supported on structs",
+                ));
+            }
+            e.variants
+                .iter()
+                .filter(|v| matches!(v.fields, Fields::Named(_)))
+                .map(|v| {
+                    let variant = &v.ident;
+                    let name = variant.to_string().to_snake_case();
+                    let mut variant_config = config.clone();
+                    let attrs_config = derive_config(&v.attrs)?;
+                    if let Some(krate) = &attrs_config.krate {
+                        return Err(syn::Error::new(
+                            krate.span(),
+                            "'crate' must be specified on the enum rather than its variants",
+                        ));
+                    }
+                    variant_config.merge(attrs_config);
+                    if !variant_config.has("entry") {
+                        let entry = format!(
+                            "{}_builder",
+                            variant_config
+                                .constructor
+                                .as_ref()
+                                .map(|c| c.to_string())
+                                .unwrap_or_else(|| name.clone())
+                        );
+                        variant_config.builder.push(parse_quote!(entry = #entry));
+                    }
+                    derive_constructor(
+                        input,
+                        &v.fields,
+                        variant_config,
+                        syn::parse_str(&name)
+                            .unwrap_or_else(|_| Ident::new_raw(&name, variant.span())),
+                        quote::quote!(Self::#variant),
+                        format!("{}::{}", self_ty, variant),
+                    )
+                })
+                .collect::<syn::Result<Vec<_>>>()?
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "derive(Builder) can only be used on structs and enums",
+            ))
+        }
+    };
+
+    Ok(quote::quote! {
+        #[#buildstructor]
+        impl #impl_generics #self_ty #ty_generics #where_clause {
+            #(#constructors)*
+        }
+    })
+}
+
+fn derive_config(attrs: &[Attribute]) -> syn::Result<DeriveConfig> {
+    let mut config = DeriveConfig::default();
+    for attr in builder_attributes(attrs) {
+        config.merge(attr.parse_args()?);
+    }
+    Ok(config)
+}
+
+fn derive_constructor(
+    input: &DeriveInput,
+    fields: &Fields,
+    config: DeriveConfig,
+    default_constructor: Ident,
+    construct: TokenStream2,
+    description: String,
+) -> syn::Result<TokenStream2> {
+    let vis = &input.vis.to_token_stream().to_string();
+    let (_, ty_generics, _) = &input.generics.split_for_impl();
+    let self_ty = &input.ident;
+    let constructor = config
+        .constructor
+        .clone()
+        .unwrap_or_else(|| default_constructor.clone());
+    let mut builder_config = config.builder.clone();
+    if !config.has("visibility") {
+        builder_config.push(parse_quote!(visibility = #vis));
+    }
+    if constructor != default_constructor && !config.has("entry") {
+        builder_config.push(parse_quote!(entry = "builder"));
+    }
+
+    let field_names: Vec<Ident> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| f.ident.clone().unwrap_or_else(|| format_ident!("_{}", idx)))
+        .collect();
+    let parameters: Vec<TokenStream2> = fields
+        .iter()
+        .zip(&field_names)
+        .map(|(f, name)| {
+            let ty = &f.ty;
+            if f.ident.is_some() {
+                let attrs = builder_attributes(&f.attrs);
+                return Ok(quote::quote! {
+                    #(#attrs)* #name : #ty
+                });
+            }
+            // Positional fields get a setter named after their position unless one is given.
+            let mut metas = Vec::new();
+            for attr in builder_attributes(&f.attrs) {
+                metas
+                    .extend(attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?);
+            }
+            if !metas.iter().any(|m| m.path().is_ident("setter")) {
+                let setter = name.to_string();
+                metas.push(parse_quote!(setter = #setter));
+            }
+            Ok(quote::quote! {
+                #[builder(#(#metas),*)] #name : #ty
+            })
+        })
+        .collect::<syn::Result<_>>()?;
+    let construct = match fields {
+        Fields::Named(_) => quote::quote!(#construct { #(#field_names),* }),
+        Fields::Unnamed(_) => quote::quote!(#construct ( #(#field_names),* )),
+        Fields::Unit => construct,
+    };
+
+    let arguments_doc = fields
+        .iter()
+        .zip(&field_names)
+        .map(|(f, name)| {
+            format!(
+                "* `{}`: {}{}",
+                name,
+                f.attrs
+                    .iter()
+                    .filter(|a| a.path().get_ident() == Some(&format_ident!("doc")))
+                    .map(|a| {
+                        let doc = a.to_token_stream().to_string();
+                        let trimmed = doc[doc.find('\"').unwrap_or_default() + 1
+                            ..doc.rfind('\"').unwrap_or(doc.len())]
+                            .trim()
+                            .to_string();
+                        trimmed
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                if f.ty.last_ident() == Some(format_ident!("Option")) || has_default(f) {
+                    " (optional)"
+                } else {
+                    ""
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let constructor_doc = format!(
+        "Create a new {}\n\n # Arguments\n\n{}",
+        description, arguments_doc
+    );
+
+    Ok(quote::quote! {
+        #[doc=#constructor_doc]
+        #[builder(#(#builder_config),*)]
+        fn #constructor(
+            #(#parameters),*
+        )->#self_ty #ty_generics{
+            #construct
+        }
+    })
+}
+
+fn builder_attributes(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|a| a.path().get_ident() == Some(&format_ident!("builder")))
+        .collect()
+}
+
+fn has_default(field: &Field) -> bool {
+    builder_attributes(&field.attrs).iter().any(|a| {
+        a.parse_args::<FieldConfig>()
+            .map(|c| c.default.is_some())
+            .unwrap_or_default()
+    })
+}
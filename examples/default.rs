@@ -0,0 +1,30 @@
+use buildstructor::buildstructor;
+use std::time::Duration;
+
+pub struct Defaults {
+    retries: usize,
+    timeout: Duration,
+}
+
+#[buildstructor]
+impl Defaults {
+    #[builder]
+    fn new(
+        #[builder(default)] retries: usize,
+        #[builder(default = Duration::from_secs(30))] timeout: Duration,
+    ) -> Defaults {
+        Self { retries, timeout }
+    }
+}
+
+fn main() {
+    let defaults = Defaults::builder().build();
+    assert_eq!(defaults.retries, 0);
+    assert_eq!(defaults.timeout, Duration::from_secs(30));
+    let defaults = Defaults::builder()
+        .retries(3)
+        .timeout(Duration::from_secs(1))
+        .build();
+    assert_eq!(defaults.retries, 3);
+    assert_eq!(defaults.timeout, Duration::from_secs(1));
+}
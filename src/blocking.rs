@@ -0,0 +1,55 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Runs a future to completion on the current thread.
+///
+/// This is the minimal executor used by `#[builder(blocking_exit = "...")]`. It provides no IO or timers,
+/// so futures that need a runtime such as tokio should be driven through [`BlockOn`] instead.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// A runtime that can drive a future to completion from sync code, used by the `<blocking_exit>_on` exit.
+///
+/// With the `tokio` feature this is implemented for `tokio::runtime::Handle` and `tokio::runtime::Runtime`.
+pub trait BlockOn {
+    /// Runs the future to completion, blocking the current thread.
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Handle {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Handle::block_on(self, future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Runtime {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Runtime::block_on(self, future)
+    }
+}
@@ -0,0 +1,123 @@
+//! Traits used by `#[builder(collection)]` and `#[builder(map_collection)]` parameters.
+//!
+//! Parameters are normally recognized as collections by their type name, which does not work for newtypes or third party collections.
+//! Marking a parameter with `#[builder(collection)]` gives it setters that add items through [`Collection`] instead, so any type that implements it can be used.
+//! The type must also implement `Default`, as the builder starts from an empty collection.
+//!
+//! The traits are implemented for the std collections, and with the `smallvec`, `arrayvec` and `http` features for `SmallVec`, `ArrayVec` and `HeaderMap`.
+//! The orphan rule stops you implementing them for other third party types, wrap those in a newtype instead:
+//!
+//! ```rust
+//! use buildstructor::Collection;
+//!
+//! #[derive(Default)]
+//! pub struct Hosts(Vec<String>);
+//!
+//! impl<T: Into<String>> Collection<T> for Hosts {
+//!     fn insert_item(&mut self, item: T) {
+//!         self.0.push(item.into())
+//!     }
+//! }
+//!
+//! pub struct Client {
+//!     hosts: Hosts,
+//! }
+//!
+//! #[buildstructor::buildstructor]
+//! impl Client {
+//!     #[builder]
+//!     fn new(#[builder(collection)] hosts: Hosts) -> Client {
+//!         Self { hosts }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let client = Client::builder().host("a").hosts(["b", "c"]).build();
+//!     assert_eq!(client.hosts.0, vec!["a", "b", "c"]);
+//! }
+//! ```
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::hash::{BuildHasher, Hash};
+
+/// A collection that items can be added to one at a time.
+pub trait Collection<Item> {
+    /// Adds an item to the collection.
+    fn insert_item(&mut self, item: Item);
+}
+
+/// A map that entries can be added to one at a time.
+pub trait MapCollection<K, V> {
+    /// Adds an entry to the map.
+    fn insert_entry(&mut self, key: K, value: V);
+}
+
+impl<T> Collection<T> for Vec<T> {
+    fn insert_item(&mut self, item: T) {
+        self.push(item)
+    }
+}
+
+impl<T> Collection<T> for VecDeque<T> {
+    fn insert_item(&mut self, item: T) {
+        self.push_back(item)
+    }
+}
+
+impl<T> Collection<T> for LinkedList<T> {
+    fn insert_item(&mut self, item: T) {
+        self.push_back(item)
+    }
+}
+
+impl<T: Ord> Collection<T> for BinaryHeap<T> {
+    fn insert_item(&mut self, item: T) {
+        self.push(item)
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher> Collection<T> for HashSet<T, S> {
+    fn insert_item(&mut self, item: T) {
+        self.insert(item);
+    }
+}
+
+impl<T: Ord> Collection<T> for BTreeSet<T> {
+    fn insert_item(&mut self, item: T) {
+        self.insert(item);
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> MapCollection<K, V> for HashMap<K, V, S> {
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+impl<K: Ord, V> MapCollection<K, V> for BTreeMap<K, V> {
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.insert(key, value);
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Collection<A::Item> for smallvec::SmallVec<A> {
+    fn insert_item(&mut self, item: A::Item) {
+        self.push(item)
+    }
+}
+
+/// Panics if the `ArrayVec` is full.
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> Collection<T> for arrayvec::ArrayVec<T, CAP> {
+    fn insert_item(&mut self, item: T) {
+        self.push(item)
+    }
+}
+
+/// Entries are appended, so a header may be given more than once.
+#[cfg(feature = "http")]
+impl<K: http::header::IntoHeaderName, T> MapCollection<K, T> for http::HeaderMap<T> {
+    fn insert_entry(&mut self, key: K, value: T) {
+        self.append(key, value);
+    }
+}
@@ -0,0 +1,182 @@
+//! The states that each field of a generated builder can be in.
+//!
+//! A builder's type records the state of every field as a tuple, with groups following the fields.
+//! Each builder alias such as `NewFooBuilder` has a companion `NewFooBuilderState` that takes this tuple as its last parameter,
+//! for instance `NewFooBuilderState<(Set<String>, Optional<Option<usize>>)>`.
+//! Use these types to name partially configured builders, so that they can be returned from functions or stored.
+//!
+//! ```rust
+//! use buildstructor::state::{Optional, Set};
+//!
+//! pub struct Server {
+//!     name: String,
+//!     port: Option<u16>,
+//! }
+//!
+//! #[buildstructor::buildstructor]
+//! impl Server {
+//!     #[builder]
+//!     fn new(name: String, port: Option<u16>) -> Server {
+//!         Self { name, port }
+//!     }
+//! }
+//!
+//! fn named(builder: NewServerBuilder) -> NewServerBuilderState<(Set<String>, Optional<Option<u16>>)> {
+//!     builder.name("server")
+//! }
+//!
+//! fn main() {
+//!     let server = named(Server::builder()).port(8080).build();
+//!     assert_eq!(server.name, "server");
+//! }
+//! ```
+
+use std::mem::MaybeUninit;
+
+/// A required field that has not been set yet.
+pub struct Required<T> {
+    _uninit: MaybeUninit<T>,
+}
+
+impl<T> Required<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new() -> Self {
+        Required {
+            _uninit: MaybeUninit::uninit(),
+        }
+    }
+}
+
+/// An optional field, or a collection that may have been added to.
+pub struct Optional<T> {
+    lazy: Option<T>,
+}
+
+impl<T> Optional<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new(lazy: Option<T>) -> Self {
+        Optional { lazy }
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn lazy_mut(&mut self) -> &mut Option<T> {
+        &mut self.lazy
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn into_lazy(self) -> Option<T> {
+        self.lazy
+    }
+}
+
+/// A field that has been set.
+pub struct Set<T> {
+    value: T,
+}
+
+impl<T> Set<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Set { value }
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+/// A field that was copied from an existing value by `to_builder()`, it may be overridden.
+pub struct Existing<T> {
+    value: T,
+}
+
+impl<T> Existing<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Existing { value }
+    }
+}
+
+/// A group where no member has been set.
+pub struct GroupUnset;
+
+/// A group where a member has been set.
+pub struct GroupSet;
+
+/// A group whose members were copied from an existing value by `to_builder()`.
+pub struct GroupExisting;
+
+/// What happens to the other members of an exclusive group in state `G` when one of them is set.
+/// Members copied by `to_builder()` are discarded, so that the new member is the only one set.
+#[doc(hidden)]
+pub trait Discard<G> {
+    type Output;
+    fn discard(self) -> Self::Output;
+}
+
+impl<T> Discard<GroupUnset> for Optional<T> {
+    type Output = Optional<T>;
+    #[inline(always)]
+    fn discard(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T> Discard<GroupExisting> for Existing<T> {
+    type Output = Optional<T>;
+    #[inline(always)]
+    fn discard(self) -> Self::Output {
+        Optional { lazy: None }
+    }
+}
+
+// The group settable trait already rejects this state, so the members are left alone to keep the error message focused.
+impl<S> Discard<GroupSet> for S {
+    type Output = S;
+    #[inline(always)]
+    fn discard(self) -> Self::Output {
+        self
+    }
+}
+
+impl<T: Default> From<Optional<T>> for Set<T> {
+    #[inline(always)]
+    fn from(o: Optional<T>) -> Self {
+        Set {
+            value: o.lazy.unwrap_or_default(),
+        }
+    }
+}
+
+impl<T> From<Set<T>> for Optional<T> {
+    #[inline(always)]
+    fn from(s: Set<T>) -> Self {
+        Optional {
+            lazy: Some(s.value),
+        }
+    }
+}
+
+impl<T> From<Existing<T>> for Set<T> {
+    #[inline(always)]
+    fn from(e: Existing<T>) -> Self {
+        Set { value: e.value }
+    }
+}
+
+impl<T> From<Existing<T>> for Optional<T> {
+    #[inline(always)]
+    fn from(e: Existing<T>) -> Self {
+        Optional {
+            lazy: Some(e.value),
+        }
+    }
+}
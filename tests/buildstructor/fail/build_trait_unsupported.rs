@@ -0,0 +1,36 @@
+use buildstructor::{buildstructor, Build};
+use std::time::Instant;
+
+pub struct Report {
+    title: String,
+    generated: Instant,
+}
+
+#[buildstructor]
+impl Report {
+    #[builder]
+    fn new(#[builder(exit_arg)] generated: Instant, title: String) -> Report {
+        Self { title, generated }
+    }
+}
+
+pub struct Page {
+    title: String,
+}
+
+#[buildstructor]
+impl Page {
+    #[builder]
+    async fn new(title: String) -> Page {
+        Self { title }
+    }
+}
+
+fn finish<B: Build>(builder: B) -> B::Output {
+    builder.build()
+}
+
+fn main() {
+    let _ = finish(Report::builder().title("report"));
+    let _ = finish(Page::builder().title("page"));
+}
@@ -0,0 +1,17 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    names: Vec<String>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(#[builder(collection = "list")] names: Vec<String>) -> Foo {
+        Foo { names }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().name("a").build();
+}
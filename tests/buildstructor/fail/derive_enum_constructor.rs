@@ -0,0 +1,7 @@
+#[derive(buildstructor::Builder)]
+#[builder(constructor = "create")]
+pub enum Event {
+    Created { id: usize },
+}
+
+fn main() {}
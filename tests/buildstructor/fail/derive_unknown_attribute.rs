@@ -0,0 +1,7 @@
+#[derive(buildstructor::Builder)]
+#[builder(name = "create")]
+pub struct Foo {
+    simple: usize,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use buildstructor::buildstructor;
+pub struct Foo {
+    simple: Option<usize>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(simple: Option<usize>) -> Foo {
+        Self { simple }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().simple(1).and_simple(Some(2)).build();
+}
@@ -0,0 +1,17 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    simple: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(#[builder(exit_arg, default)] simple: usize) -> Foo {
+        Foo { simple }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().build(3);
+}
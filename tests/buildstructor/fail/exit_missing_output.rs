@@ -0,0 +1,17 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    simple: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(exit(name = "build_rc", map = std::rc::Rc::new))]
+    fn new(simple: usize) -> Foo {
+        Foo { simple }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().simple(3).build_rc();
+}
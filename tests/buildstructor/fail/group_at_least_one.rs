@@ -0,0 +1,20 @@
+use buildstructor::buildstructor;
+
+pub struct Connection {}
+
+#[buildstructor]
+impl Connection {
+    #[builder]
+    fn new(
+        #[builder(group = "address", at_least_one)] url: Option<String>,
+        #[builder(group = "address")] host: Option<String>,
+    ) -> Connection {
+        let _ = (url, host);
+        Self {}
+    }
+}
+
+fn main() {
+    let _ = Connection::builder().build();
+    let _ = Connection::builder().and_url(None::<String>).build();
+}
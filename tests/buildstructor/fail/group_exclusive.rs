@@ -0,0 +1,27 @@
+use buildstructor::buildstructor;
+
+pub struct Client {
+    password: Option<String>,
+    token: Option<String>,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder(to_builder = "to_builder")]
+    fn new(
+        #[builder(group = "auth", exclusive)] password: Option<String>,
+        #[builder(group = "auth")] token: Option<String>,
+    ) -> Client {
+        let _ = (password, token);
+        Self {
+            password: None,
+            token: None,
+        }
+    }
+}
+
+fn main() {
+    let _ = Client::builder().password("p").token("t").build();
+    let client = Client::builder().password("p").build();
+    let _ = client.to_builder().password("q").token("t").build();
+}
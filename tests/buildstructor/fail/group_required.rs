@@ -0,0 +1,20 @@
+use buildstructor::buildstructor;
+
+pub struct Client {}
+
+#[buildstructor]
+impl Client {
+    #[builder]
+    fn new(
+        #[builder(group = "auth", exclusive, required)] password: Option<String>,
+        #[builder(group = "auth")] token: Option<String>,
+    ) -> Client {
+        let _ = (password, token);
+        Self {}
+    }
+}
+
+fn main() {
+    let _ = Client::builder().build();
+    let _ = Client::builder().and_token(None::<String>).build();
+}
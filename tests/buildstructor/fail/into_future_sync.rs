@@ -0,0 +1,17 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    simple: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(into_future = true)]
+    fn new(simple: usize) -> Foo {
+        Foo { simple }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().simple(3).build();
+}
@@ -0,0 +1,21 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    name: String,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(name: String) -> Foo {
+        Self { name }
+    }
+}
+
+fn register(foo: impl Into<Foo>) -> Foo {
+    foo.into()
+}
+
+fn main() {
+    let _ = register(Foo::builder());
+}
@@ -0,0 +1,17 @@
+use buildstructor::buildstructor;
+pub struct Foo {
+    name: String,
+    count: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(name: String, count: usize) -> Foo {
+        Self { name, count }
+    }
+}
+
+fn main() {
+    let _ = Foo::builder().count(2).build();
+}
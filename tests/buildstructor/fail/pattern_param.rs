@@ -0,0 +1,18 @@
+use buildstructor::buildstructor;
+
+pub struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[buildstructor]
+impl Point {
+    #[builder]
+    fn new((x, y): (u32, u32)) -> Point {
+        Self { x, y }
+    }
+}
+
+fn main() {
+    let _ = Point::builder().build();
+}
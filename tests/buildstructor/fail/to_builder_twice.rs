@@ -0,0 +1,10 @@
+#[derive(buildstructor::Builder)]
+#[builder(to_builder = "to_builder")]
+pub struct Foo {
+    name: String,
+}
+
+fn main() {
+    let foo = Foo::builder().name("a").build();
+    let _ = foo.to_builder().name("b").name("c").build();
+}
@@ -0,0 +1,46 @@
+use arrayvec::ArrayVec;
+use buildstructor::buildstructor;
+use http::{HeaderMap, HeaderValue};
+use smallvec::SmallVec;
+
+pub struct Request {
+    hosts: SmallVec<[String; 2]>,
+    ports: ArrayVec<u16, 4>,
+    headers: HeaderMap,
+}
+
+#[buildstructor]
+impl Request {
+    #[builder(dynamic)]
+    fn new(
+        #[builder(collection)] hosts: SmallVec<[String; 2]>,
+        #[builder(collection)] ports: ArrayVec<u16, 4>,
+        #[builder(map_collection)] headers: HeaderMap,
+    ) -> Request {
+        Self {
+            hosts,
+            ports,
+            headers,
+        }
+    }
+}
+
+fn main() {
+    let request = Request::builder()
+        .host("a".to_string())
+        .hosts(["b".to_string()])
+        .port(80)
+        .ports([443, 8080])
+        .header("accept", HeaderValue::from_static("*/*"))
+        .header(http::header::ACCEPT, HeaderValue::from_static("text/html"))
+        .build();
+    assert_eq!(request.hosts.as_slice(), ["a", "b"]);
+    assert_eq!(request.ports.as_slice(), [80, 443, 8080]);
+    assert_eq!(request.headers.get_all("accept").iter().count(), 2);
+
+    let mut builder = Request::dyn_builder();
+    builder.port(1).header("x-id", HeaderValue::from_static("1"));
+    let request = builder.build().unwrap();
+    assert_eq!(request.ports.as_slice(), [1]);
+    assert!(request.hosts.is_empty());
+}
@@ -0,0 +1,46 @@
+use buildstructor::{buildstructor, BlockOn};
+use std::future::Future;
+
+pub struct Foo {
+    simple: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(blocking_exit = "build_blocking")]
+    async fn new(simple: usize) -> Foo {
+        Foo { simple }
+    }
+}
+
+#[derive(Default)]
+pub struct Client;
+
+#[buildstructor]
+impl Client {
+    #[builder(entry = "request", exit = "send", blocking_exit = "send_blocking")]
+    async fn request_send(&self, path: String) -> Result<String, String> {
+        tokio::task::yield_now().await;
+        Ok(path)
+    }
+}
+
+struct Tokio(tokio::runtime::Runtime);
+
+impl BlockOn for Tokio {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+fn main() {
+    let foo = Foo::builder().simple(3).build_blocking();
+    assert_eq!(foo.simple, 3);
+
+    let runtime = Tokio(tokio::runtime::Runtime::new().unwrap());
+    let client = Client;
+    let response = client.request().path("/foo").send_blocking_on(&runtime);
+    assert_eq!(response, Ok("/foo".to_string()));
+    let response = client.request().path("/bar").send_blocking();
+    assert_eq!(response, Ok("/bar".to_string()));
+}
@@ -0,0 +1,74 @@
+use buildstructor::buildstructor;
+use buildstructor::state::{Existing, GroupSet, Optional, Set};
+
+pub struct Server {
+    name: String,
+    port: Option<u16>,
+    tags: Vec<String>,
+}
+
+#[buildstructor]
+impl Server {
+    #[builder]
+    fn new(name: String, port: Option<u16>, tags: Vec<String>) -> Server {
+        Self { name, port, tags }
+    }
+}
+
+fn defaults(
+    builder: NewServerBuilder,
+) -> NewServerBuilderState<(Set<String>, Set<Option<u16>>, Optional<Vec<String>>)> {
+    builder.name("default").port(80)
+}
+
+fn build(
+    builder: NewServerBuilderState<(Set<String>, Set<Option<u16>>, Optional<Vec<String>>)>,
+) -> Server {
+    builder.tag("web").build()
+}
+
+#[derive(buildstructor::Builder)]
+#[builder(to_builder = "to_builder")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+fn existing<T>(wrapper: Wrapper<T>) -> NewWrapperBuilderState<T, (Existing<T>,)> {
+    wrapper.to_builder()
+}
+
+pub struct Client {
+    password: Option<String>,
+    token: Option<String>,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder]
+    fn new(
+        #[builder(group = "auth", exclusive)] password: Option<String>,
+        #[builder(group = "auth", exclusive)] token: Option<String>,
+    ) -> Client {
+        Self { password, token }
+    }
+}
+
+fn with_token(
+    builder: NewClientBuilder,
+) -> NewClientBuilderState<(Optional<Option<String>>, Set<Option<String>>, GroupSet)> {
+    builder.token("t")
+}
+
+fn main() {
+    let server = build(defaults(Server::builder()));
+    assert_eq!(server.name, "default");
+    assert_eq!(server.port, Some(80));
+    assert_eq!(server.tags, vec!["web".to_string()]);
+
+    let wrapper = existing(Wrapper::builder().value(1).build()).value(2).build();
+    assert_eq!(wrapper.value, 2);
+
+    let client = with_token(Client::builder()).build();
+    assert_eq!(client.password, None);
+    assert_eq!(client.token, Some("t".to_string()));
+}
@@ -0,0 +1,94 @@
+use buildstructor::buildstructor;
+use std::collections::{LinkedList, VecDeque};
+
+#[derive(Default)]
+pub struct ConfigMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+pub struct NodeList<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Default)]
+pub struct IdBag<T> {
+    ids: Vec<T>,
+}
+
+impl<T> IdBag<T> {
+    fn add(&mut self, id: T) {
+        self.ids.push(id);
+    }
+}
+
+impl<T> Extend<T> for IdBag<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.ids.extend(iter)
+    }
+}
+
+impl<T> IntoIterator for IdBag<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ids.into_iter()
+    }
+}
+
+pub struct Foo {
+    jobs: VecDeque<String>,
+    steps: LinkedList<String>,
+    config: ConfigMap<String, String>,
+    ids: IdBag<usize>,
+    nodes: NodeList<String>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(dynamic)]
+    fn new(
+        jobs: VecDeque<String>,
+        steps: LinkedList<String>,
+        #[builder(collection = "none")] config: ConfigMap<String, String>,
+        #[builder(collection = "vec", insert = "add")] ids: IdBag<usize>,
+        nodes: NodeList<String>,
+    ) -> Foo {
+        Self {
+            jobs,
+            steps,
+            config,
+            ids,
+            nodes,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder()
+        .job("a")
+        .job("b")
+        .step("c")
+        .config(ConfigMap {
+            entries: vec![("d".to_string(), "e".to_string())],
+        })
+        .id(1)
+        .id(2)
+        .nodes(NodeList {
+            nodes: vec!["f".to_string()],
+        })
+        .build();
+    assert_eq!(foo.jobs, vec!["a", "b"]);
+    assert_eq!(foo.steps.front().map(String::as_str), Some("c"));
+    assert_eq!(foo.config.entries.len(), 1);
+    assert_eq!(foo.ids.ids, vec![1, 2]);
+    assert_eq!(foo.nodes.nodes, vec!["f"]);
+
+    let mut builder = Foo::dyn_builder();
+    builder.job("a").step("b").id(3);
+    builder.config(ConfigMap::default());
+    builder.nodes(NodeList { nodes: vec![] });
+    let foo = builder.build().unwrap();
+    assert_eq!(foo.jobs.back().map(String::as_str), Some("a"));
+    assert_eq!(foo.ids.ids, vec![3]);
+}
@@ -0,0 +1,69 @@
+use buildstructor::{buildstructor, Build};
+use std::convert::TryFrom;
+
+#[derive(Debug)]
+pub struct ServerConfig {
+    name: String,
+    port: Option<u16>,
+}
+
+#[buildstructor]
+impl ServerConfig {
+    #[builder]
+    fn new(name: String, port: Option<u16>) -> ServerConfig {
+        Self { name, port }
+    }
+
+    #[builder(entry = "checked", exit = "check")]
+    fn checked_new(name: String) -> Result<Self, String> {
+        if name.is_empty() {
+            Err("empty name".to_string())
+        } else {
+            Ok(Self { name, port: None })
+        }
+    }
+}
+
+fn register(config: impl Into<ServerConfig>) -> ServerConfig {
+    config.into()
+}
+
+fn finish<B: Build>(builder: B) -> B::Output {
+    builder.build()
+}
+
+#[derive(buildstructor::Builder)]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[derive(Default)]
+pub struct Client;
+
+#[buildstructor]
+impl Client {
+    #[builder(entry = "ping", exit = "send")]
+    fn ping_call(&self, message: String) -> String {
+        message
+    }
+}
+
+fn main() {
+    let config = register(ServerConfig::builder().name("a").port(80));
+    assert_eq!(config.name, "a");
+    assert_eq!(config.port, Some(80));
+
+    let config = ServerConfig::try_from(ServerConfig::checked().name("b")).unwrap();
+    assert_eq!(config.name, "b");
+    assert!(ServerConfig::try_from(ServerConfig::checked().name("")).is_err());
+
+    let config = finish(ServerConfig::builder().name("c"));
+    assert_eq!(config.port, None);
+    assert!(finish(ServerConfig::checked().name("")).is_err());
+
+    let wrapper: Wrapper<usize> = Wrapper::builder().value(1).into();
+    assert_eq!(wrapper.value, 1);
+
+    let client = Client;
+    assert_eq!(finish(client.ping().message("hi")), "hi");
+}
@@ -0,0 +1,46 @@
+use ::buildstructor as bs;
+use std::collections::HashMap;
+
+// Hides the runtime crate so that generated code can only reach it through `bs`.
+#[allow(dead_code)]
+mod buildstructor {}
+
+pub struct Client {
+    name: String,
+    tags: Vec<String>,
+    labels: HashMap<String, String>,
+}
+
+#[bs::buildstructor(crate = bs)]
+impl Client {
+    #[builder(dynamic)]
+    fn new(name: String, tags: Vec<String>, labels: HashMap<String, String>) -> Client {
+        Self { name, tags, labels }
+    }
+}
+
+#[derive(bs::Builder)]
+#[builder(crate = bs, to_builder = "to_builder")]
+pub struct Config {
+    name: String,
+    #[builder(collection)]
+    hosts: Vec<String>,
+}
+
+fn existing(config: Config) -> NewConfigBuilderState<(bs::state::Existing<String>, bs::state::Optional<Vec<String>>)> {
+    config.to_builder()
+}
+
+fn main() {
+    let client = Client::builder().name("a").tag("t").label("k", "v").build();
+    assert_eq!(client.name, "a");
+    assert_eq!(client.tags, vec!["t".to_string()]);
+    assert_eq!(client.labels.len(), 1);
+
+    let client = Client::dyn_builder().name("b").build().unwrap();
+    assert_eq!(client.name, "b");
+
+    let config = Config::builder().name("c").host("h".to_string()).build();
+    let config = existing(config).host("i".to_string()).build();
+    assert_eq!(config.hosts, vec!["h".to_string(), "i".to_string()]);
+}
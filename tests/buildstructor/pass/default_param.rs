@@ -0,0 +1,49 @@
+use buildstructor::buildstructor;
+use std::time::Duration;
+
+pub struct Foo {
+    simple: usize,
+    defaulted: usize,
+    timeout: Duration,
+    tags: Vec<String>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(
+        simple: usize,
+        #[builder(default)] defaulted: usize,
+        #[builder(default = Duration::from_secs(30))] timeout: Duration,
+        #[builder(default = vec!["default".to_string()])] tags: Vec<String>,
+    ) -> Foo {
+        Self {
+            simple,
+            defaulted,
+            timeout,
+            tags,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder().simple(2).build();
+    assert_eq!(foo.simple, 2);
+    assert_eq!(foo.defaulted, 0);
+    assert_eq!(foo.timeout, Duration::from_secs(30));
+    assert_eq!(foo.tags, vec!["default".to_string()]);
+
+    let foo = Foo::builder()
+        .simple(2)
+        .defaulted(3)
+        .timeout(Duration::from_secs(1))
+        .tag("custom")
+        .build();
+    assert_eq!(foo.defaulted, 3);
+    assert_eq!(foo.timeout, Duration::from_secs(1));
+    assert_eq!(foo.tags, vec!["custom".to_string()]);
+
+    // The plain constructor still takes every argument.
+    let foo = Foo::new(1, 2, Duration::from_secs(3), vec![]);
+    assert_eq!(foo.defaulted, 2);
+}
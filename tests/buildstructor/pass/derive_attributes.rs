@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+#[derive(buildstructor::Builder)]
+#[builder(constructor = "create", exit = "finish")]
+pub struct Renamed {
+    #[builder(default = 3)]
+    count: usize,
+    #[builder(into = false)]
+    name: String,
+    #[builder(setter = "label")]
+    tag: Option<String>,
+    #[builder(skip)]
+    cache: HashMap<String, usize>,
+}
+
+impl Renamed {
+    #[allow(dead_code)]
+    fn new() -> Renamed {
+        Renamed::create(0, String::new(), None, HashMap::new())
+    }
+}
+
+#[derive(buildstructor::Builder)]
+#[builder(entry = "make", visibility = "pub(crate)")]
+pub struct Entry {
+    simple: usize,
+}
+
+fn main() {
+    let renamed = Renamed::builder()
+        .name(String::from("a"))
+        .label("b")
+        .finish();
+    assert_eq!(renamed.count, 3);
+    assert_eq!(renamed.name, "a");
+    assert_eq!(renamed.tag, Some("b".to_string()));
+    assert!(renamed.cache.is_empty());
+
+    let entry = Entry::make().simple(2).build();
+    assert_eq!(entry.simple, 2);
+}
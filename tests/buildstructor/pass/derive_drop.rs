@@ -0,0 +1,21 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(buildstructor::Builder)]
+pub struct Guard {
+    name: String,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn main() {
+    let guard = Guard::builder().name("a").build();
+    assert_eq!(guard.name, "a");
+    drop(guard);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+
+#[derive(buildstructor::Builder)]
+pub enum Event {
+    Created {
+        id: usize,
+        at: Option<u64>,
+        tags: HashSet<String>,
+    },
+    #[builder(constructor = "remove", exit = "finish")]
+    Deleted {
+        id: usize,
+        #[builder(default = String::from("unknown"))]
+        reason: String,
+    },
+    Type {
+        name: String,
+    },
+    Tick(usize),
+    Empty,
+}
+
+fn main() {
+    let created = Event::created_builder().id(1).tag("a").tag("b").build();
+    assert!(matches!(created, Event::Created { id: 1, at: None, tags } if tags.len() == 2));
+
+    let deleted = Event::remove_builder().id(2).finish();
+    assert!(matches!(deleted, Event::Deleted { id: 2, reason } if reason == "unknown"));
+
+    let typed = Event::type_builder().name("a").build();
+    assert!(matches!(typed, Event::Type { name } if name == "a"));
+
+    let _ = Event::Tick(1);
+    let _ = Event::Empty;
+}
@@ -0,0 +1,15 @@
+#[derive(buildstructor::Builder)]
+pub struct Config {
+    name: String,
+}
+
+impl Config {
+    pub fn to_builder(&self) -> String {
+        format!("builder for {}", self.name)
+    }
+}
+
+fn main() {
+    let config = Config::builder().name("a").build();
+    assert_eq!(config.to_builder(), "builder for a");
+}
@@ -0,0 +1,22 @@
+#[derive(buildstructor::Builder)]
+pub struct Point(usize, usize);
+
+#[derive(buildstructor::Builder)]
+pub struct Named(
+    #[builder(setter = "id")] usize,
+    #[builder(default)] String,
+    Option<usize>,
+    Vec<String>,
+);
+
+fn main() {
+    let point = Point::builder()._0(1)._1(2).build();
+    assert_eq!(point.0, 1);
+    assert_eq!(point.1, 2);
+
+    let named = Named::builder().id(3)._2(4).build();
+    assert_eq!(named.0, 3);
+    assert_eq!(named.1, "");
+    assert_eq!(named.2, Some(4));
+    assert!(named.3.is_empty());
+}
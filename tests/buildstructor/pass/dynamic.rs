@@ -0,0 +1,90 @@
+use buildstructor::{buildstructor, MissingFields};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct Server {
+    name: String,
+    port: u16,
+    timeout: u64,
+    description: Option<String>,
+    hosts: Vec<String>,
+    labels: HashMap<String, String>,
+}
+
+#[buildstructor]
+impl Server {
+    #[builder(dynamic)]
+    fn new(
+        name: String,
+        port: u16,
+        #[builder(default = 30)] timeout: u64,
+        description: Option<String>,
+        hosts: Vec<String>,
+        labels: HashMap<String, String>,
+    ) -> Server {
+        Self {
+            name,
+            port,
+            timeout,
+            description,
+            hosts,
+            labels,
+        }
+    }
+}
+
+pub struct Holder {
+    builder: NewServerDynBuilder,
+}
+
+#[derive(Debug)]
+pub struct Generic<T> {
+    value: T,
+}
+
+#[buildstructor]
+impl<T> Generic<T> {
+    #[builder(dynamic)]
+    async fn new(value: T) -> Generic<T> {
+        Self { value }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut holder = Holder {
+        builder: Server::dyn_builder(),
+    };
+    for (key, value) in [("name", "server"), ("host", "h1"), ("host", "h2")] {
+        match key {
+            "name" => holder.builder.name(value),
+            "host" => holder.builder.host(value),
+            _ => unreachable!(),
+        };
+    }
+    holder.builder.label("k", "v").description("d");
+
+    let missing: MissingFields = holder.builder.build().unwrap_err();
+    assert_eq!(missing.fields(), &["port"]);
+    assert_eq!(
+        missing.to_string(),
+        "required fields were not set on `Server::dyn_builder()`: `port`"
+    );
+
+    let server = holder.builder.port(8080u16).build().unwrap();
+    assert_eq!(server.name, "server");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.timeout, 30);
+    assert_eq!(server.description, Some("d".to_string()));
+    assert_eq!(server.hosts.len(), 2);
+    assert_eq!(server.labels.len(), 1);
+
+    let mut builders = vec![Server::dyn_builder(), Server::dyn_builder()];
+    for builder in &mut builders {
+        builder.name("a").port(1u16).timeout(5);
+    }
+    assert_eq!(builders[1].build().unwrap().timeout, 5);
+
+    let generic = Generic::dyn_builder().value(1).build().await.unwrap();
+    assert_eq!(generic.value, 1);
+}
@@ -0,0 +1,41 @@
+//! Dynamic builders must not trip `missing_docs` in the crates that use them.
+#![deny(missing_docs)]
+
+use std::collections::HashMap;
+
+/// A client.
+pub struct Client {
+    name: String,
+    port: Option<u16>,
+    tags: Vec<String>,
+    headers: Option<HashMap<String, String>>,
+}
+
+#[buildstructor::buildstructor]
+impl Client {
+    /// Create a client.
+    #[builder(dynamic)]
+    pub fn new(
+        name: String,
+        port: Option<u16>,
+        tags: Vec<String>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Client {
+        Self {
+            name,
+            port,
+            tags,
+            headers,
+        }
+    }
+}
+
+fn main() {
+    let mut builder = Client::dyn_builder();
+    builder.name("a").port(80).tag("t").header("k", "v");
+    let client = builder.build().unwrap();
+    assert_eq!(client.name, "a");
+    assert_eq!(client.port, Some(80));
+    assert_eq!(client.tags, vec!["t"]);
+    assert_eq!(client.headers.map(|h| h.len()), Some(1));
+}
@@ -0,0 +1,71 @@
+use buildstructor::buildstructor;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub struct Transaction {
+    statements: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Client;
+
+#[buildstructor]
+impl Client {
+    #[builder(entry = "message", exit = "send")]
+    fn message_send(
+        &self,
+        #[builder(exit_arg)] transaction: &mut Transaction,
+        statement: String,
+        #[builder(exit_arg)] deadline: Instant,
+    ) -> bool {
+        transaction.statements.push(statement);
+        deadline > Instant::now()
+    }
+
+    #[builder(entry = "request", exit = "send", blocking_exit = "send_blocking")]
+    async fn request_send(&self, path: String, #[builder(exit_arg)] retries: usize) -> String {
+        format!("{} x{}", path, retries)
+    }
+}
+
+pub struct Report {
+    title: String,
+    generated: Instant,
+}
+
+#[buildstructor]
+impl Report {
+    #[builder(exit_arc = "build_arc", dynamic)]
+    fn new(#[builder(exit_arg)] generated: Instant, title: String) -> Report {
+        Self { title, generated }
+    }
+}
+
+fn main() {
+    let client = Client;
+    let mut transaction = Transaction::default();
+    let deadline = Instant::now() + Duration::from_secs(60);
+    assert!(client
+        .message()
+        .statement("select 1")
+        .send(&mut transaction, deadline));
+    assert!(client
+        .message()
+        .statement("select 2")
+        .send(&mut transaction, deadline));
+    assert_eq!(transaction.statements, vec!["select 1", "select 2"]);
+
+    assert_eq!(client.request().path("/foo").send_blocking(3), "/foo x3");
+
+    let now = Instant::now();
+    let report = Report::builder().title("daily").build(now);
+    assert_eq!(report.title, "daily");
+    assert_eq!(report.generated, now);
+    let report = Report::builder().title("weekly").build_arc(now);
+    assert_eq!(report.title, "weekly");
+
+    let mut builder = Report::dyn_builder();
+    assert!(builder.build(now).is_err());
+    builder.title("monthly");
+    assert_eq!(builder.build(now).unwrap().title, "monthly");
+}
@@ -0,0 +1,61 @@
+use buildstructor::buildstructor;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+pub struct Service {
+    name: String,
+}
+
+impl Named for Service {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[buildstructor]
+impl Service {
+    #[builder(
+        exit_arc = "build_arc",
+        exit_boxed = "build_boxed",
+        exit(name = "build_rc", map = Rc::new, output = "Rc<Self>")
+    )]
+    fn new(name: String) -> Service {
+        Self { name }
+    }
+
+    #[builder(entry = "checked", exit = "check", exit_arc = "check_arc")]
+    fn checked_new(name: String) -> Result<Self, String> {
+        if name.is_empty() {
+            Err("empty name".to_string())
+        } else {
+            Ok(Self { name })
+        }
+    }
+
+    #[builder(entry = "connect", exit_boxed = "build_boxed")]
+    async fn connect_new(name: String) -> Service {
+        Self { name }
+    }
+}
+
+fn main() {
+    let service: Arc<Service> = Service::builder().name("a").build_arc();
+    assert_eq!(service.name, "a");
+
+    let service: Box<dyn Named> = Service::builder().name("b").build_boxed();
+    assert_eq!(service.name(), "b");
+
+    let service: Rc<Service> = Service::builder().name("c").build_rc();
+    assert_eq!(service.name, "c");
+
+    let service: Result<Arc<Service>, String> = Service::checked().name("d").check_arc();
+    assert_eq!(service.unwrap().name, "d");
+    assert!(Service::checked().name("").check_arc().is_err());
+
+    let service = buildstructor::block_on(Service::connect().name("e").build_boxed());
+    assert_eq!(service.name, "e");
+}
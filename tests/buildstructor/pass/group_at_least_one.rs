@@ -0,0 +1,36 @@
+use buildstructor::buildstructor;
+
+pub struct Connection {
+    url: Option<String>,
+    host: Option<String>,
+    socket_path: Option<String>,
+}
+
+#[buildstructor]
+impl Connection {
+    #[builder]
+    fn new(
+        #[builder(group = "address", at_least_one)] url: Option<String>,
+        #[builder(group = "address")] host: Option<String>,
+        #[builder(group = "address")] socket_path: Option<String>,
+    ) -> Connection {
+        Self {
+            url,
+            host,
+            socket_path,
+        }
+    }
+}
+
+fn main() {
+    let connection = Connection::builder().host("localhost").build();
+    assert_eq!(connection.host, Some("localhost".to_string()));
+    assert_eq!(connection.url, None);
+
+    let connection = Connection::builder()
+        .socket_path("/tmp/socket")
+        .url("http://localhost")
+        .build();
+    assert_eq!(connection.socket_path, Some("/tmp/socket".to_string()));
+    assert_eq!(connection.url, Some("http://localhost".to_string()));
+}
@@ -0,0 +1,81 @@
+use buildstructor::buildstructor;
+
+pub struct Client {
+    password: Option<String>,
+    token: Option<String>,
+    port: u16,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder]
+    fn new(
+        #[builder(group = "auth", exclusive)] password: Option<String>,
+        #[builder(group = "auth")] token: Option<String>,
+        #[builder(default = 80)] port: u16,
+    ) -> Client {
+        Self {
+            password,
+            token,
+            port,
+        }
+    }
+
+    #[builder(entry = "connect", exit = "open")]
+    fn connect_new(
+        #[builder(group = "auth", exclusive, required)] password: Option<String>,
+        #[builder(group = "auth")] token: Option<String>,
+        #[builder(group = "port", exclusive, default = 80)] port: u16,
+    ) -> Client {
+        Self {
+            password,
+            token,
+            port,
+        }
+    }
+}
+
+#[derive(buildstructor::Builder)]
+#[builder(to_builder = "to_builder")]
+pub struct Derived {
+    #[builder(group = "source", exclusive, required)]
+    path: Option<String>,
+    #[builder(group = "source")]
+    url: Option<String>,
+    #[builder(group = "limit", exclusive, default = 3)]
+    retries: u32,
+    #[builder(group = "limit")]
+    timeout: Option<u32>,
+}
+
+fn main() {
+    let client = Client::builder().build();
+    assert_eq!(client.password, None);
+    assert_eq!(client.port, 80);
+
+    let client = Client::builder().port(1).password("p").build();
+    assert_eq!(client.password, Some("p".to_string()));
+    assert_eq!(client.token, None);
+
+    let client = Client::connect().token("t").open();
+    assert_eq!(client.token, Some("t".to_string()));
+
+    let client = Client::connect().port(8080).password("p").open();
+    assert_eq!(client.port, 8080);
+
+    let derived = Derived::builder().url("u").retries(5).build();
+    let derived = derived.to_builder().build();
+    assert_eq!(derived.url, Some("u".to_string()));
+    assert_eq!(derived.retries, 5);
+
+    // Setting a member of a copied exclusive group replaces the members that were copied.
+    let derived = derived.to_builder().url("v").build();
+    assert_eq!(derived.url, Some("v".to_string()));
+    assert_eq!(derived.retries, 5);
+
+    let derived = derived.to_builder().path("p").timeout(10).build();
+    assert_eq!(derived.path, Some("p".to_string()));
+    assert_eq!(derived.url, None);
+    assert_eq!(derived.retries, 3);
+    assert_eq!(derived.timeout, Some(10));
+}
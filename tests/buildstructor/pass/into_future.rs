@@ -0,0 +1,75 @@
+use buildstructor::buildstructor;
+use std::rc::Rc;
+
+pub struct Foo {
+    simple: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(into_future = true)]
+    async fn new(simple: usize) -> Foo {
+        Foo { simple }
+    }
+}
+
+pub struct Generic<T> {
+    value: T,
+}
+
+#[buildstructor]
+impl<T> Generic<T> {
+    #[builder(into_future = true)]
+    async fn new(value: T) -> Generic<T> {
+        Generic { value }
+    }
+}
+
+#[derive(Default)]
+pub struct Client {
+    prefix: String,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder(entry = "request", exit = "send", into_future = true)]
+    async fn request_send(&self, path: String) -> String {
+        format!("{}{}", self.prefix, path)
+    }
+}
+
+pub struct Local {
+    shared: Rc<usize>,
+}
+
+#[buildstructor]
+impl Local {
+    #[builder]
+    async fn new(shared: Rc<usize>) -> Local {
+        let value = shared.clone();
+        std::future::ready(()).await;
+        Local { shared: value }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let foo = Foo::builder().simple(3).await;
+    assert_eq!(foo.simple, 3);
+
+    let spawned = tokio::spawn(async { Foo::builder().simple(4).await })
+        .await
+        .unwrap();
+    assert_eq!(spawned.simple, 4);
+
+    let generic = Generic::builder().value("a").await;
+    assert_eq!(generic.value, "a");
+
+    let client = Client {
+        prefix: "/api".to_string(),
+    };
+    assert_eq!(client.request().path("/foo").await, "/api/foo");
+
+    let local = Local::builder().shared(Rc::new(1)).build().await;
+    assert_eq!(*local.shared, 1);
+}
@@ -0,0 +1,48 @@
+use buildstructor::buildstructor;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub struct Foo {
+    name: Cow<'static, str>,
+    path: PathBuf,
+    count: Option<u64>,
+    tags: Vec<String>,
+    labels: HashMap<String, Arc<str>>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(
+        #[builder(into)] name: Cow<'static, str>,
+        #[builder(into = false)] path: PathBuf,
+        #[builder(into)] count: Option<u64>,
+        #[builder(element_into = false)] tags: Vec<String>,
+        #[builder(key_into = false, value_into)] labels: HashMap<String, Arc<str>>,
+    ) -> Foo {
+        Self {
+            name,
+            path,
+            count,
+            tags,
+            labels,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder()
+        .name("static")
+        .path(PathBuf::from("/tmp"))
+        .count(3u8)
+        .tag("a".to_string())
+        .label("k".to_string(), "v")
+        .build();
+    assert_eq!(foo.name, "static");
+    assert_eq!(foo.path, PathBuf::from("/tmp"));
+    assert_eq!(foo.count, Some(3));
+    assert_eq!(foo.tags, vec!["a".to_string()]);
+    assert_eq!(&*foo.labels["k"], "v");
+}
@@ -0,0 +1,89 @@
+use buildstructor::buildstructor;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Default)]
+pub struct Hosts(Vec<String>);
+
+impl<T: Into<String>> buildstructor::Collection<T> for Hosts {
+    fn insert_item(&mut self, item: T) {
+        self.0.push(item.into())
+    }
+}
+
+pub struct Patch {
+    names: Option<Vec<String>>,
+    jobs: Option<VecDeque<u16>>,
+    headers: Option<HashMap<String, String>>,
+    hosts: Option<Hosts>,
+    ids: Option<Vec<usize>>,
+}
+
+#[buildstructor]
+impl Patch {
+    #[builder(dynamic)]
+    fn new(
+        names: Option<Vec<String>>,
+        jobs: Option<VecDeque<u16>>,
+        headers: Option<HashMap<String, String>>,
+        #[builder(collection)] hosts: Option<Hosts>,
+        #[builder(collection = "none")] ids: Option<Vec<usize>>,
+    ) -> Patch {
+        Self {
+            names,
+            jobs,
+            headers,
+            hosts,
+            ids,
+        }
+    }
+}
+
+fn main() {
+    let patch = Patch::builder().build();
+    assert_eq!(patch.names, None);
+    assert_eq!(patch.jobs, None);
+    assert_eq!(patch.headers, None);
+    assert!(patch.hosts.is_none());
+    assert_eq!(patch.ids, None);
+
+    let patch = Patch::builder()
+        .name("a")
+        .names(["b"])
+        .job(1)
+        .header("accept", "*/*")
+        .host("h")
+        .ids(vec![1])
+        .build();
+    assert_eq!(patch.names, Some(vec!["a".to_string(), "b".to_string()]));
+    assert_eq!(patch.jobs, Some(VecDeque::from([1])));
+    assert_eq!(patch.headers.map(|h| h.len()), Some(1));
+    assert_eq!(patch.hosts.map(|h| h.0), Some(vec!["h".to_string()]));
+    assert_eq!(patch.ids, Some(vec![1]));
+
+    // Setting an empty collection is different from leaving it unset.
+    let patch = Patch::builder().names(Vec::<String>::new()).headers(HashMap::<String, String>::new()).build();
+    assert_eq!(patch.names, Some(vec![]));
+    assert_eq!(patch.headers, Some(HashMap::new()));
+
+    let patch = Patch::builder()
+        .and_names(Some(vec!["a".to_string()]))
+        .name("b")
+        .and_jobs(None)
+        .and_headers(Some(HashMap::new()))
+        .and_hosts(None)
+        .build();
+    assert_eq!(patch.names, Some(vec!["a".to_string(), "b".to_string()]));
+    assert_eq!(patch.jobs, None);
+    assert_eq!(patch.headers, Some(HashMap::new()));
+    assert!(patch.hosts.is_none());
+
+    let patch = Patch::builder().name("a").and_names(None).build();
+    assert_eq!(patch.names, None);
+
+    let mut builder = Patch::dyn_builder();
+    builder.name("a").and_jobs(Some(VecDeque::from([2])));
+    let patch = builder.build().unwrap();
+    assert_eq!(patch.names, Some(vec!["a".to_string()]));
+    assert_eq!(patch.jobs, Some(VecDeque::from([2])));
+    assert_eq!(patch.headers, None);
+}
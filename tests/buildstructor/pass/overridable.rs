@@ -0,0 +1,61 @@
+use buildstructor::buildstructor;
+
+pub struct Foo {
+    name: String,
+    count: Option<usize>,
+    path: String,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(
+        #[builder(overridable)] name: String,
+        #[builder(overridable)] count: Option<usize>,
+        path: String,
+    ) -> Foo {
+        Self { name, count, path }
+    }
+}
+
+pub struct Layered {
+    name: String,
+    timeout: u64,
+    hosts: Vec<String>,
+}
+
+#[buildstructor(overridable)]
+impl Layered {
+    #[builder]
+    fn new(name: String, #[builder(default = 30)] timeout: u64, hosts: Vec<String>) -> Layered {
+        Self {
+            name,
+            timeout,
+            hosts,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder()
+        .name("a")
+        .count(1)
+        .path("p")
+        .name("b")
+        .and_count(None)
+        .build();
+    assert_eq!(foo.name, "b");
+    assert_eq!(foo.count, None);
+    assert_eq!(foo.path, "p");
+
+    let layered = Layered::builder()
+        .name("default")
+        .timeout(10)
+        .host("h1")
+        .name("user")
+        .timeout(20)
+        .build();
+    assert_eq!(layered.name, "user");
+    assert_eq!(layered.timeout, 20);
+    assert_eq!(layered.hosts.len(), 1);
+}
@@ -0,0 +1,71 @@
+use buildstructor::buildstructor;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+pub struct Foo {
+    names: Vec<String>,
+    ports: VecDeque<u16>,
+    tags: HashSet<String>,
+    headers: HashMap<String, String>,
+    limits: BTreeMap<String, usize>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder(dynamic)]
+    fn new(
+        names: Vec<String>,
+        ports: VecDeque<u16>,
+        tags: HashSet<String>,
+        headers: HashMap<String, String>,
+        limits: BTreeMap<String, usize>,
+    ) -> Foo {
+        Self {
+            names,
+            ports,
+            tags,
+            headers,
+            limits,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder()
+        .names(["a", "b"])
+        .names(vec!["c".to_string()])
+        .name("d")
+        .ports((1..3).map(|p| p * 10))
+        .tags(HashSet::from(["x"]))
+        .headers([("accept", "*/*")])
+        .limits(vec![("requests".to_string(), 10)])
+        .build();
+    assert_eq!(foo.names, vec!["a", "b", "c", "d"]);
+    assert_eq!(foo.ports, vec![10, 20]);
+    assert!(foo.tags.contains("x"));
+    assert_eq!(foo.headers.get("accept").map(String::as_str), Some("*/*"));
+    assert_eq!(foo.limits.get("requests"), Some(&10));
+
+    let mut builder = Foo::dyn_builder();
+    builder.names(["a"]).ports([80]).headers([("x", "y")]);
+    let foo = builder.build().unwrap();
+    assert_eq!(foo.names, vec!["a"]);
+    assert_eq!(foo.ports, vec![80]);
+    assert_eq!(foo.headers.len(), 1);
+
+    let foo = Foo::builder()
+        .names(Vec::<String>::new())
+        .ports(vec![])
+        .tags(Vec::<&str>::new())
+        .tag("t")
+        .headers(HashMap::<String, String>::new())
+        .limits(BTreeMap::<String, usize>::new())
+        .build();
+    assert!(foo.names.is_empty());
+    assert!(foo.ports.is_empty());
+    assert_eq!(foo.tags.len(), 1);
+    assert!(foo.limits.is_empty());
+
+    let mut builder = Foo::dyn_builder();
+    builder.ports(vec![]).tags([] as [&str; 0]);
+    assert!(builder.build().unwrap().tags.is_empty());
+}
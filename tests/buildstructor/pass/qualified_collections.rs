@@ -0,0 +1,61 @@
+use buildstructor::buildstructor;
+use std::collections;
+
+pub trait Settings {
+    type Map;
+}
+
+pub struct Defaults;
+
+impl Settings for Defaults {
+    type Map = collections::BTreeMap<String, String>;
+}
+
+pub struct Foo<T: Settings> {
+    names: ::std::vec::Vec<String>,
+    tags: std::collections::HashSet<String>,
+    headers: collections::HashMap<String, String>,
+    port: std::option::Option<u16>,
+    settings: T::Map,
+}
+
+#[buildstructor]
+impl<T: Settings> Foo<T> {
+    #[builder]
+    fn new(
+        names: ::std::vec::Vec<String>,
+        tags: std::collections::HashSet<String>,
+        headers: collections::HashMap<String, String>,
+        port: std::option::Option<u16>,
+        settings: T::Map,
+    ) -> Foo<T> {
+        Self {
+            names,
+            tags,
+            headers,
+            port,
+            settings,
+        }
+    }
+}
+
+fn main() {
+    let foo: Foo<Defaults> = Foo::builder()
+        .name("a")
+        .tag("b")
+        .header("c", "d")
+        .port(80)
+        .settings(collections::BTreeMap::new())
+        .build();
+    assert_eq!(foo.names, vec!["a".to_string()]);
+    assert!(foo.tags.contains("b"));
+    assert_eq!(foo.headers.get("c"), Some(&"d".to_string()));
+    assert_eq!(foo.port, Some(80));
+    assert!(foo.settings.is_empty());
+
+    let foo: Foo<Defaults> = Foo::builder()
+        .settings(collections::BTreeMap::new())
+        .build();
+    assert!(foo.names.is_empty());
+    assert_eq!(foo.port, None);
+}
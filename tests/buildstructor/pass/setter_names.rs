@@ -0,0 +1,45 @@
+use buildstructor::buildstructor;
+use std::collections::HashMap;
+
+pub struct Foo {
+    ty: String,
+    cfg: Option<String>,
+    data: Vec<String>,
+    header_map: HashMap<String, String>,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(
+        #[builder(setter = "r#type")] ty: String,
+        #[builder(setter = "config")] cfg: Option<String>,
+        #[builder(singular = "datum")] data: Vec<String>,
+        #[builder(singular = "header", plural = "headers")] header_map: HashMap<String, String>,
+    ) -> Foo {
+        Self {
+            ty,
+            cfg,
+            data,
+            header_map,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder()
+        .r#type("json")
+        .config("debug")
+        .datum("a")
+        .data(vec!["b".to_string()])
+        .header("accept", "json")
+        .headers(HashMap::from([("host".to_string(), "localhost".to_string())]))
+        .build();
+    assert_eq!(foo.ty, "json");
+    assert_eq!(foo.cfg, Some("debug".to_string()));
+    assert_eq!(foo.data, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(foo.header_map.len(), 2);
+
+    let foo = Foo::builder().r#type("xml").and_config(None::<String>).build();
+    assert_eq!(foo.cfg, None);
+}
@@ -0,0 +1,64 @@
+use buildstructor::buildstructor;
+use std::time::Duration;
+
+pub struct Client {
+    timeout: Duration,
+    retries: Option<usize>,
+    headers: Vec<String>,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder]
+    fn new(timeout: Duration, retries: Option<usize>, headers: Vec<String>) -> Client {
+        Self {
+            timeout,
+            retries,
+            headers,
+        }
+    }
+}
+
+fn apply_retries<B: NewClientBuilderSetsRetries>(builder: B) -> B::Output {
+    builder.retries(3)
+}
+
+fn apply_tracing<B>(builder: B) -> <B::Output as NewClientBuilderSetsHeaders>::Output
+where
+    B: NewClientBuilderSetsTimeout,
+    B::Output: NewClientBuilderSetsHeaders,
+{
+    builder.timeout(Duration::from_secs(5)).header("traceparent")
+}
+
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[buildstructor]
+impl<T> Wrapper<T> {
+    #[builder]
+    fn new(value: T) -> Wrapper<T> {
+        Self { value }
+    }
+}
+
+fn with_value<T, B: NewWrapperBuilderSetsValue<T>>(builder: B, value: T) -> B::Output {
+    builder.value(value)
+}
+
+fn main() {
+    let client = apply_tracing(Client::builder()).build();
+    assert_eq!(client.timeout, Duration::from_secs(5));
+    assert_eq!(client.retries, None);
+    assert_eq!(client.headers, vec!["traceparent".to_string()]);
+
+    let client = apply_tracing(apply_retries(Client::builder())).build();
+    assert_eq!(client.retries, Some(3));
+
+    let client = apply_retries(Client::builder().timeout(Duration::from_secs(1))).build();
+    assert_eq!(client.timeout, Duration::from_secs(1));
+
+    let wrapper = with_value(Wrapper::builder(), 1).build();
+    assert_eq!(wrapper.value, 1);
+}
@@ -0,0 +1,53 @@
+use buildstructor::buildstructor;
+
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        100
+    }
+}
+
+pub struct FakeClock(u64);
+impl Clock for FakeClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+pub struct Foo {
+    simple: usize,
+    started: u64,
+    retries: usize,
+}
+
+#[buildstructor]
+impl Foo {
+    #[builder]
+    fn new(
+        simple: usize,
+        #[builder(skip = Box::new(SystemClock))] clock: Box<dyn Clock>,
+        #[builder(skip)] retries: usize,
+    ) -> Foo {
+        Self {
+            simple,
+            started: clock.now(),
+            retries,
+        }
+    }
+}
+
+fn main() {
+    let foo = Foo::builder().simple(2).build();
+    assert_eq!(foo.simple, 2);
+    assert_eq!(foo.started, 100);
+    assert_eq!(foo.retries, 0);
+
+    // The plain constructor still accepts the skipped arguments.
+    let foo = Foo::new(2, Box::new(FakeClock(5)), 3);
+    assert_eq!(foo.started, 5);
+    assert_eq!(foo.retries, 3);
+}
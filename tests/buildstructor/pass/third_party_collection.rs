@@ -0,0 +1,34 @@
+use buildstructor::{buildstructor, MapCollection};
+use multimap::MultiMap;
+
+// The orphan rule stops `MapCollection` being implemented for `MultiMap` directly.
+#[derive(Default)]
+pub struct Labels(MultiMap<String, String>);
+
+impl<K: Into<String>, V: Into<String>> MapCollection<K, V> for Labels {
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.0.insert(key.into(), value.into())
+    }
+}
+
+pub struct Pod {
+    labels: Labels,
+}
+
+#[buildstructor]
+impl Pod {
+    #[builder]
+    fn new(#[builder(map_collection)] labels: Labels) -> Pod {
+        Self { labels }
+    }
+}
+
+fn main() {
+    let pod = Pod::builder()
+        .label("tier", "web")
+        .label("tier", "cache")
+        .labels([("app", "shop")])
+        .build();
+    assert_eq!(pod.labels.0.get_vec("tier").map(Vec::len), Some(2));
+    assert_eq!(pod.labels.0.get("app").map(String::as_str), Some("shop"));
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+#[derive(buildstructor::Builder, Clone)]
+#[builder(to_builder = "to_builder")]
+pub struct Config {
+    name: String,
+    #[builder(default = 30)]
+    timeout: u64,
+    retries: Option<usize>,
+    hosts: Vec<String>,
+    labels: HashMap<String, String>,
+}
+
+#[derive(buildstructor::Builder)]
+#[builder(to_builder = "to_builder")]
+pub struct Point(usize, #[builder(setter = "y")] usize);
+
+#[derive(buildstructor::Builder)]
+#[builder(to_builder = "modify")]
+pub struct Generic<T> {
+    value: T,
+}
+
+fn main() {
+    let config = Config::builder()
+        .name("a")
+        .retries(3)
+        .host("h1")
+        .label("k", "v")
+        .build();
+
+    let unchanged = config.clone().to_builder().build();
+    assert_eq!(unchanged.name, "a");
+    assert_eq!(unchanged.timeout, 30);
+    assert_eq!(unchanged.retries, Some(3));
+
+    let changed = config
+        .to_builder()
+        .name("b")
+        .timeout(10)
+        .and_retries(None)
+        .host("h2")
+        .build();
+    assert_eq!(changed.name, "b");
+    assert_eq!(changed.timeout, 10);
+    assert_eq!(changed.retries, None);
+    assert_eq!(changed.hosts, vec!["h1".to_string(), "h2".to_string()]);
+    assert_eq!(changed.labels.len(), 1);
+
+    let point = Point::builder()._0(1).y(2).build().to_builder().y(3).build();
+    assert_eq!(point.0, 1);
+    assert_eq!(point.1, 3);
+
+    let generic = Generic::builder().value(1).build().modify().value(2).build();
+    assert_eq!(generic.value, 2);
+}
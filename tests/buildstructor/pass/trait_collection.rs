@@ -0,0 +1,70 @@
+use buildstructor::{buildstructor, Collection, MapCollection};
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+pub struct Hosts(Vec<String>);
+
+impl<T: Into<String>> Collection<T> for Hosts {
+    fn insert_item(&mut self, item: T) {
+        self.0.push(item.into())
+    }
+}
+
+#[derive(Default)]
+pub struct Headers(Vec<(String, String)>);
+
+impl<K: Into<String>, V: Into<String>> MapCollection<K, V> for Headers {
+    fn insert_entry(&mut self, key: K, value: V) {
+        self.0.push((key.into(), value.into()))
+    }
+}
+
+pub struct Client {
+    hosts: Hosts,
+    headers: Headers,
+    ports: Vec<u16>,
+    limits: BTreeMap<String, usize>,
+}
+
+#[buildstructor]
+impl Client {
+    #[builder(dynamic)]
+    fn new(
+        #[builder(collection)] hosts: Hosts,
+        #[builder(map_collection)] headers: Headers,
+        #[builder(collection)] ports: Vec<u16>,
+        #[builder(map_collection)] limits: BTreeMap<String, usize>,
+    ) -> Client {
+        Self {
+            hosts,
+            headers,
+            ports,
+            limits,
+        }
+    }
+}
+
+fn main() {
+    let client = Client::builder()
+        .host("a")
+        .hosts(["b".to_string(), "c".to_string()])
+        .header("accept", "*/*")
+        .headers(vec![("x-id", "1")])
+        .port(80)
+        .ports(443..444)
+        .limit("requests".to_string(), 10)
+        .build();
+    assert_eq!(client.hosts.0, vec!["a", "b", "c"]);
+    assert_eq!(client.headers.0.len(), 2);
+    assert_eq!(client.ports, vec![80, 443]);
+    assert_eq!(client.limits.get("requests"), Some(&10));
+
+    let client = Client::builder().build();
+    assert!(client.hosts.0.is_empty());
+
+    let mut builder = Client::dyn_builder();
+    builder.host("a").header("accept", "*/*").port(80);
+    let client = builder.build().unwrap();
+    assert_eq!(client.hosts.0, vec!["a"]);
+    assert_eq!(client.ports, vec![80]);
+}
@@ -3,4 +3,6 @@ fn ui() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/buildstructor/fail/*.rs");
     t.pass("tests/buildstructor/pass/*.rs");
+    #[cfg(all(feature = "smallvec", feature = "arrayvec", feature = "http"))]
+    t.pass("tests/buildstructor/features/*.rs");
 }